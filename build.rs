@@ -20,15 +20,30 @@ fn main() {
         any_bindings = true;
     }
 
+    println!("cargo:rustc-check-cfg=cfg(gsl_romberg)");
+    println!("cargo:rustc-check-cfg=cfg(gsl_fixed)");
+
     if any_bindings {
         let out_path = PathBuf::from(env::var("OUT_DIR").unwrap());
-        bindings.blacklist_item("FP_NORMAL")
-                .blacklist_item("FP_SUBNORMAL")
-                .blacklist_item("FP_ZERO")
-                .blacklist_item("FP_INFINITE")
-                .blacklist_item("FP_NAN")
-                .generate().unwrap()
-                .write_to_file(out_path.join("integrand_bindings.rs"))
+        let bindings = bindings.blacklist_item("FP_NORMAL")
+                               .blacklist_item("FP_SUBNORMAL")
+                               .blacklist_item("FP_ZERO")
+                               .blacklist_item("FP_INFINITE")
+                               .blacklist_item("FP_NAN")
+                               .generate().unwrap();
+
+        // Romberg integration was added in GSL 2.5, so only wrap it if the
+        // installed GSL provides it
+        if bindings.to_string().contains("fn gsl_integration_romberg(") {
+            println!("cargo:rustc-cfg=gsl_romberg");
+        }
+
+        // Likewise, fixed-point quadratures were added in GSL 2.3
+        if bindings.to_string().contains("fn gsl_integration_fixed_alloc(") {
+            println!("cargo:rustc-cfg=gsl_fixed");
+        }
+
+        bindings.write_to_file(out_path.join("integrand_bindings.rs"))
                 .expect("Error writing bindings");
     }
 }
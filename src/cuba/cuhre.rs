@@ -1,24 +1,33 @@
 use std::{mem, ptr};
-use std::os::raw::{c_int, c_longlong};
+use std::os::raw::{c_int, c_longlong, c_void};
+use std::path::{Path, PathBuf};
 
 use ::bindings;
-use ::ffi::LandingPad;
-use ::traits::{IntegrandInput, IntegrandOutput};
+use ::traits::{BatchIntegrand, BatchIntegrator, IntegrandInput, IntegrandOutput};
 use ::{Integrator, Real};
 
-use super::{cuba_integrand, CubaError, CubaIntegrationResult, CubaIntegrationResults};
+use super::{batch_dims, batch_integrand, cuba_batch_integrand, cuba_integrand,
+            cuba_parallel_integrand, integrand_dims, statefile_cstring, thread_count,
+            CubaBatchIntegrand, CubaError, CubaFlags, CubaIntegrand, CubaIntegrationResult,
+            CubaIntegrationResults, Hyperrectangle, DEFAULT_NVEC};
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Clone, Debug)]
 pub struct Cuhre {
     pub mineval: usize,
     pub maxeval: usize,
     key: Option<u16>,
+    domain: Option<Hyperrectangle>,
+    nvec: usize,
+    threads: Option<usize>,
+    flags: CubaFlags,
+    statefile: Option<PathBuf>,
 }
 
 impl Cuhre {
     pub fn new(maxeval: usize) -> Self {
         Cuhre {
-            mineval: 1, maxeval, key: None
+            mineval: 1, maxeval, key: None, domain: None,
+            nvec: DEFAULT_NVEC, threads: None, flags: CubaFlags::default(), statefile: None
         }
     }
 
@@ -43,25 +52,121 @@ impl Cuhre {
             })
         }
     }
-}
 
-impl Integrator for Cuhre {
-    type Success = CubaIntegrationResults;
-    type Failure = super::CubaError;
-    fn integrate<A, B, F: FnMut(A) -> B>(&mut self, mut fun: F, epsrel: Real, epsabs: Real) -> Result<Self::Success, Self::Failure>
+    /// Integrate over `domain`, rather than Cuba's unit hypercube. The
+    /// integrand is called with arguments in `domain`, and the Jacobian of
+    /// the transformation is applied automatically.
+    pub fn with_domain(self, domain: Hyperrectangle) -> Self {
+        Cuhre {
+            domain: Some(domain), ..self
+        }
+    }
+
+    /// Integrate over Cuba's unit hypercube. (Default)
+    pub fn without_domain(self) -> Self {
+        Cuhre {
+            domain: None, ..self
+        }
+    }
+
+    pub fn domain(&self) -> Option<&Hyperrectangle> {
+        self.domain.as_ref()
+    }
+
+    /// Save the state of the integration to the file at `path` as it goes,
+    /// and resume from that state, if the file exists, when integration
+    /// starts. So, an integration which is interrupted can be resumed from
+    /// where it left off, by integrating the same integrand again with the
+    /// same parameters. Unless `with_retain_statefile(true)` is set, the
+    /// file is deleted once the integration succeeds.
+    pub fn with_statefile<P: AsRef<Path>>(self, path: P) -> Self {
+        Cuhre {
+            statefile: Some(path.as_ref().to_path_buf()), ..self
+        }
+    }
+
+    /// Don't save the state of the integration. (Default)
+    pub fn without_statefile(self) -> Self {
+        Cuhre {
+            statefile: None, ..self
+        }
+    }
+
+    pub fn statefile(&self) -> Option<&Path> {
+        self.statefile.as_ref().map(|path| path.as_path())
+    }
+
+    /// Keep the state file after the integration succeeds, e.g. to
+    /// continue the integration to a tighter tolerance later.
+    /// (Default = false)
+    pub fn with_retain_statefile(self, retain: bool) -> Self {
+        Cuhre {
+            flags: self.flags.with_retain_statefile(retain), ..self
+        }
+    }
+
+    /// Set all of Cuba's flags at once, including those set by
+    /// `with_retain_statefile`. See `CubaFlags`. (Default = no flags)
+    pub fn with_flags(self, flags: CubaFlags) -> Self {
+        Cuhre {
+            flags, ..self
+        }
+    }
+
+    pub fn flags(&self) -> CubaFlags {
+        self.flags
+    }
+
+    /// The largest number of points passed to the integrand at once by
+    /// `integrate_parallel`, to be split between threads, and by
+    /// `integrate_batch`. (Default = 1024)
+    pub fn with_nvec(self, nvec: usize) -> Self {
+        Cuhre {
+            nvec: nvec.max(1), ..self
+        }
+    }
+
+    /// The number of threads `integrate_parallel` evaluates the integrand
+    /// on. (Default = the available parallelism of the machine)
+    pub fn with_threads(self, threads: usize) -> Self {
+        Cuhre {
+            threads: Some(threads.max(1)), ..self
+        }
+    }
+
+    /// Integrates like `integrate`, but has Cuba pass the integrand batches
+    /// of up to `nvec` points (see `with_nvec`), which are evaluated in
+    /// parallel on Rust threads (see `with_threads`). A panic in the
+    /// integrand, on any thread, aborts the integration and is resumed here.
+    pub fn integrate_parallel<A, B, F>(&mut self, mut fun: F, epsrel: Real, epsabs: Real)
+            -> Result<CubaIntegrationResults, CubaError>
         where A: IntegrandInput,
-              B: IntegrandOutput
+              B: IntegrandOutput,
+              F: Fn(A) -> B + Sync
     {
-        // Using cuba's parallelization via fork() would deeply break Rust's
-        // concurrency model and safety guarantees. So, we'll turn it off.
-        unsafe { bindings::cubacores(0, 0) };
+        let (ndim, ncomp) = integrand_dims("cuhre", &mut fun, self.domain.as_ref())?;
 
-        let (ndim, ncomp) = {
-            let inputs = A::input_size();
-            let outputs = fun(A::from_args(&vec![0.5; inputs][..])).output_size();
-            (inputs, outputs)
+        let mut integrand = CubaIntegrand::new(fun, self.domain.as_ref())
+                                          .with_threads(thread_count(self.threads));
+        let res = unsafe {
+            self.run(ndim, ncomp,
+                     batch_integrand(cuba_parallel_integrand::<A, B, F>), mem::transmute(&mut integrand),
+                     self.nvec, epsrel, epsabs)
         };
+        integrand.maybe_resume_unwind();
+        res
+    }
+
+    /// Runs Cuhre on `integrand`, which is called with `userdata` and
+    /// batches of up to `nvec` points.
+    unsafe fn run(&self, ndim: usize, ncomp: usize,
+                  integrand: bindings::integrand_t, userdata: *mut c_void, nvec: usize,
+                  epsrel: Real, epsabs: Real) -> Result<CubaIntegrationResults, CubaError> {
+        // Using cuba's parallelization via fork() would deeply break Rust's
+        // concurrency model and safety guarantees. So, we'll turn it off.
+        bindings::cubacores(0, 0);
 
+        let statefile = statefile_cstring(self.statefile.as_ref())?;
         let mut nregions = 0;
         let mut neval = 0;
         let mut fail = 0;
@@ -77,29 +182,24 @@ impl Integrator for Cuhre {
 
         assert!([7, 9, 11, 13].contains(&key));
 
-        let mut lp = LandingPad::new(fun);
-        unsafe {
-            bindings::llCuhre(ndim as c_int, ncomp as c_int,
-                              Some(cuba_integrand::<A, B, F>), mem::transmute(&mut lp),
-                              1 /* nvec */,
-                              epsrel,
-                              epsabs,
-                              0 /* flags */,
-                              self.mineval as c_longlong,
-                              self.maxeval as c_longlong,
-                              key as c_int,
-                              // statefile
-                              ptr::null(),
-                              // spin
-                              ptr::null_mut(),
-                              &mut nregions,
-                              &mut neval,
-                              &mut fail,
-                              value.as_mut_ptr(),
-                              error.as_mut_ptr(),
-                              prob.as_mut_ptr());
-        }
-        lp.maybe_resume_unwind();
+        bindings::llCuhre(ndim as c_int, ncomp as c_int,
+                          integrand, userdata,
+                          nvec as c_longlong,
+                          epsrel,
+                          epsabs,
+                          self.flags.bits(),
+                          self.mineval as c_longlong,
+                          self.maxeval as c_longlong,
+                          key as c_int,
+                          statefile.as_ref().map(|path| path.as_ptr()).unwrap_or(ptr::null()),
+                          // spin
+                          ptr::null_mut(),
+                          &mut nregions,
+                          &mut neval,
+                          &mut fail,
+                          value.as_mut_ptr(),
+                          error.as_mut_ptr(),
+                          prob.as_mut_ptr());
 
         if fail == 0 {
             Ok(CubaIntegrationResults {
@@ -132,3 +232,42 @@ impl Integrator for Cuhre {
         }
     }
 }
+
+impl Integrator for Cuhre {
+    type Success = CubaIntegrationResults;
+    type Failure = super::CubaError;
+    fn integrate<A, B, F: FnMut(A) -> B>(&mut self, mut fun: F, epsrel: Real, epsabs: Real) -> Result<Self::Success, Self::Failure>
+        where A: IntegrandInput,
+              B: IntegrandOutput
+    {
+        let (ndim, ncomp) = integrand_dims("cuhre", &mut fun, self.domain.as_ref())?;
+
+        let mut integrand = CubaIntegrand::new(fun, self.domain.as_ref());
+        let res = unsafe {
+            self.run(ndim, ncomp,
+                     Some(cuba_integrand::<A, B, F>), mem::transmute(&mut integrand),
+                     1, epsrel, epsabs)
+        };
+        integrand.maybe_resume_unwind();
+        res
+    }
+}
+
+impl BatchIntegrator for Cuhre {
+    type Success = CubaIntegrationResults;
+    type Failure = super::CubaError;
+    fn integrate_batch<I>(&mut self, integrand: I, epsrel: Real, epsabs: Real) -> Result<Self::Success, Self::Failure>
+        where I: BatchIntegrand
+    {
+        let (ndim, ncomp) = batch_dims("cuhre", &integrand, self.domain.as_ref())?;
+
+        let mut integrand = CubaBatchIntegrand::new(integrand, self.domain.as_ref());
+        let res = unsafe {
+            self.run(ndim, ncomp,
+                     batch_integrand(cuba_batch_integrand::<I>), mem::transmute(&mut integrand),
+                     self.nvec, epsrel, epsabs)
+        };
+        integrand.maybe_resume_unwind();
+        res
+    }
+}
@@ -0,0 +1,669 @@
+use std::{cmp, fmt, mem, panic, ptr, slice};
+use std::os::raw::{c_int, c_longlong, c_void};
+use std::path::{Path, PathBuf};
+
+use ::bindings;
+use ::traits::{BatchIntegrand, BatchIntegrator, IntegrandInput, IntegrandOutput};
+use ::{Integrator, Real};
+
+use super::{batch_dims, batch_integrand, batch_len, integrand_dims, statefile_cstring,
+            thread_count, CubaBatchIntegrand, CubaError, CubaFlags, CubaIntegrand,
+            CubaIntegrationResult, CubaIntegrationResults, Hyperrectangle, RandomNumberSource,
+            DEFAULT_NVEC};
+
+/// Divonne, Cuba's stratified sampling algorithm. Divonne partitions the
+/// integration region using methods from numerical optimization, so that
+/// the integrand is roughly constant on each subregion. It is particularly
+/// suited to integrands with sharp, localized peaks, especially if the
+/// locations of the peaks are known or can be found (see `with_xgiven` and
+/// `with_peakfinder`).
+///
+/// Divonne only works for integrals of two or more dimensions.
+///
+/// See Cuba's documentation [here](http://www.feynarts.de/cuba/) for details
+/// on each of the parameters.
+pub struct Divonne {
+    mineval: usize,
+    maxeval: usize,
+    seed: usize,
+    key1: c_int,
+    key2: c_int,
+    key3: c_int,
+    maxpass: usize,
+    border: Real,
+    maxchisq: Real,
+    mindeviation: Real,
+    xgiven: Vec<Vec<Real>>,
+    nextra: usize,
+    peakfinder: Option<Box<FnMut(&[(Real, Real)]) -> Vec<Vec<Real>>>>,
+    flags: CubaFlags,
+    domain: Option<Hyperrectangle>,
+    nvec: usize,
+    threads: Option<usize>,
+    statefile: Option<PathBuf>,
+}
+
+impl Default for Divonne {
+    fn default() -> Self {
+        Divonne {
+            mineval: 1,
+            maxeval: c_longlong::max_value() as usize,
+            seed: 0,
+            key1: 47,
+            key2: 1,
+            key3: 1,
+            maxpass: 5,
+            border: 0.0,
+            maxchisq: 10.0,
+            mindeviation: 0.25,
+            xgiven: Vec::new(),
+            nextra: 0,
+            peakfinder: None,
+            flags: CubaFlags::default(),
+            domain: None,
+            nvec: DEFAULT_NVEC,
+            threads: None,
+            statefile: None,
+        }
+    }
+}
+
+impl fmt::Debug for Divonne {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("Divonne")
+           .field("mineval", &self.mineval)
+           .field("maxeval", &self.maxeval)
+           .field("seed", &self.seed)
+           .field("key1", &self.key1)
+           .field("key2", &self.key2)
+           .field("key3", &self.key3)
+           .field("maxpass", &self.maxpass)
+           .field("border", &self.border)
+           .field("maxchisq", &self.maxchisq)
+           .field("mindeviation", &self.mindeviation)
+           .field("xgiven", &self.xgiven)
+           .field("nextra", &self.nextra)
+           .field("peakfinder", &self.peakfinder.is_some())
+           .field("flags", &self.flags)
+           .field("domain", &self.domain)
+           .field("nvec", &self.nvec)
+           .field("threads", &self.threads)
+           .field("statefile", &self.statefile)
+           .finish()
+    }
+}
+
+impl Divonne {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_mineval(self, mineval: usize) -> Self {
+        Divonne {
+            mineval, ..self
+        }
+    }
+
+    pub fn with_maxeval(self, maxeval: usize) -> Self {
+        Divonne {
+            maxeval, ..self
+        }
+    }
+
+    /// Set the random number generator seed. A seed of zero selects Sobol
+    /// numbers. (Default = 0)
+    pub fn with_seed(self, seed: usize) -> Self {
+        Divonne {
+            seed, ..self
+        }
+    }
+
+    /// Set the random number generator source. See `RandomNumberSource`
+    /// for how this interacts with the seed.
+    ///
+    /// # Panics
+    /// If given `RandomNumberSource::Ranlux` with a level of zero, or larger
+    /// than `CubaFlags::MAX_RANLUX_LEVEL`, panics.
+    pub fn with_rng(self, rng: RandomNumberSource) -> Self {
+        let (seed, flags) = rng.apply(self.seed, self.flags);
+        Divonne {
+            seed, flags, ..self
+        }
+    }
+
+    pub fn rng(&self) -> RandomNumberSource {
+        RandomNumberSource::from_seed_and_flags(self.seed, self.flags)
+    }
+
+    /// Sampling rule used in the partitioning phase. Values from 7 to 13
+    /// select the degree-`key1` cubature rule, any other positive value
+    /// selects a quasi-random Korobov sample of `key1` points, and negative
+    /// values select a Sobol (or pseudo-random) sample of `-key1` points.
+    /// (Default = 47)
+    pub fn with_key1(self, key1: i32) -> Self {
+        Divonne {
+            key1: key1 as c_int, ..self
+        }
+    }
+
+    /// Sampling rule used in the final integration phase, with the same
+    /// meaning as `key1`. (Default = 1)
+    pub fn with_key2(self, key2: i32) -> Self {
+        Divonne {
+            key2: key2 as c_int, ..self
+        }
+    }
+
+    /// Strategy for refining subregions in the final integration phase.
+    /// (Default = 1)
+    pub fn with_key3(self, key3: i32) -> Self {
+        Divonne {
+            key3: key3 as c_int, ..self
+        }
+    }
+
+    /// The number of passes after which the partitioning phase terminates.
+    /// (Default = 5)
+    pub fn with_maxpass(self, maxpass: usize) -> Self {
+        Divonne {
+            maxpass, ..self
+        }
+    }
+
+    /// The width of the border of the integration region. Points inside
+    /// this border are not sampled, but extrapolated from the interior.
+    /// (Default = 0)
+    pub fn with_border(self, border: Real) -> Self {
+        Divonne {
+            border, ..self
+        }
+    }
+
+    /// The maximum chi-squared value a single subregion may have in the
+    /// final integration phase before it is refined. (Default = 10)
+    pub fn with_maxchisq(self, maxchisq: Real) -> Self {
+        Divonne {
+            maxchisq, ..self
+        }
+    }
+
+    /// The minimum deviation required to refine a subregion failing the
+    /// chi-squared test. (Default = 0.25)
+    pub fn with_mindeviation(self, mindeviation: Real) -> Self {
+        Divonne {
+            mindeviation, ..self
+        }
+    }
+
+    /// Provide a list of points where the integrand might have peaks. Each
+    /// point is given in the integration domain (see `with_domain`), and
+    /// must have as many coordinates as the integrand has inputs, or
+    /// integration fails with `CubaError::BadXGiven`.
+    pub fn with_xgiven<I>(self, xgiven: I) -> Self
+        where I: IntoIterator<Item=Vec<Real>> {
+        Divonne {
+            xgiven: xgiven.into_iter().collect(), ..self
+        }
+    }
+
+    /// Provide a peakfinder, which Divonne calls with the bounds of each
+    /// subregion it examines (as `(lower, upper)` pairs, in the integration
+    /// domain), and which returns the locations of any peaks of the
+    /// integrand inside that subregion. At most `nextra` points are used
+    /// from each call; any further points are ignored.
+    ///
+    /// As with the integrand, returning a point with the wrong number of
+    /// coordinates will trigger a panic.
+    pub fn with_peakfinder<P>(self, nextra: usize, peakfinder: P) -> Self
+        where P: FnMut(&[(Real, Real)]) -> Vec<Vec<Real>> + 'static {
+        Divonne {
+            nextra,
+            peakfinder: Some(Box::new(peakfinder)),
+            ..self
+        }
+    }
+
+    /// Removes the peakfinder, if one was given.
+    pub fn without_peakfinder(self) -> Self {
+        Divonne {
+            nextra: 0,
+            peakfinder: None,
+            ..self
+        }
+    }
+
+    /// Integrate over `domain`, rather than Cuba's unit hypercube. The
+    /// integrand is called with arguments in `domain`, and the Jacobian of
+    /// the transformation is applied automatically.
+    pub fn with_domain(self, domain: Hyperrectangle) -> Self {
+        Divonne {
+            domain: Some(domain), ..self
+        }
+    }
+
+    /// Integrate over Cuba's unit hypercube. (Default)
+    pub fn without_domain(self) -> Self {
+        Divonne {
+            domain: None, ..self
+        }
+    }
+
+    pub fn domain(&self) -> Option<&Hyperrectangle> {
+        self.domain.as_ref()
+    }
+
+    /// Save the state of the integration to the file at `path` as it goes,
+    /// and resume from that state, if the file exists, when integration
+    /// starts. So, an integration which is interrupted can be resumed from
+    /// where it left off, by integrating the same integrand again with the
+    /// same parameters. Unless `with_retain_statefile(true)` is set, the
+    /// file is deleted once the integration succeeds.
+    pub fn with_statefile<P: AsRef<Path>>(self, path: P) -> Self {
+        Divonne {
+            statefile: Some(path.as_ref().to_path_buf()), ..self
+        }
+    }
+
+    /// Don't save the state of the integration. (Default)
+    pub fn without_statefile(self) -> Self {
+        Divonne {
+            statefile: None, ..self
+        }
+    }
+
+    pub fn statefile(&self) -> Option<&Path> {
+        self.statefile.as_ref().map(|path| path.as_path())
+    }
+
+    /// Keep the state file after the integration succeeds, e.g. to
+    /// continue the integration to a tighter tolerance later.
+    /// (Default = false)
+    pub fn with_retain_statefile(self, retain: bool) -> Self {
+        Divonne {
+            flags: self.flags.with_retain_statefile(retain), ..self
+        }
+    }
+
+    /// Set all of Cuba's flags at once, including those set by
+    /// `with_retain_statefile` and `with_rng`. See `CubaFlags`.
+    /// (Default = no flags)
+    pub fn with_flags(self, flags: CubaFlags) -> Self {
+        Divonne {
+            flags, ..self
+        }
+    }
+
+    pub fn flags(&self) -> CubaFlags {
+        self.flags
+    }
+
+    /// The largest number of points passed to the integrand at once by
+    /// `integrate_parallel`, to be split between threads, and by
+    /// `integrate_batch`. (Default = 1024)
+    pub fn with_nvec(self, nvec: usize) -> Self {
+        Divonne {
+            nvec: nvec.max(1), ..self
+        }
+    }
+
+    /// The number of threads `integrate_parallel` evaluates the integrand
+    /// on. (Default = the available parallelism of the machine)
+    pub fn with_threads(self, threads: usize) -> Self {
+        Divonne {
+            threads: Some(threads.max(1)), ..self
+        }
+    }
+
+    /// Integrates like `integrate`, but has Cuba pass the integrand batches
+    /// of up to `nvec` points (see `with_nvec`), which are evaluated in
+    /// parallel on Rust threads (see `with_threads`). A panic in the
+    /// integrand, on any thread, aborts the integration and is resumed here.
+    /// The peakfinder, if any, is still called on the calling thread.
+    pub fn integrate_parallel<A, B, F>(&mut self, mut fun: F, epsrel: Real, epsabs: Real)
+            -> Result<CubaIntegrationResults, CubaError>
+        where A: IntegrandInput,
+              B: IntegrandOutput,
+              F: Fn(A) -> B + Sync
+    {
+        let domain = self.domain.clone();
+        let (ndim, ncomp) = integrand_dims("divonne", &mut fun, domain.as_ref())?;
+        self.check_xgiven(ndim)?;
+
+        let integrand = CubaIntegrand::new(fun, domain.as_ref())
+                                      .with_threads(thread_count(self.threads));
+        let nvec = self.nvec;
+        unsafe {
+            self.run(ndim, ncomp, integrand,
+                     batch_integrand(divonne_parallel_integrand::<A, B, F>),
+                     nvec, epsrel, epsabs)
+        }
+    }
+
+    /// Checks that each of the points given with `with_xgiven` has `ndim`
+    /// coordinates.
+    fn check_xgiven(&self, ndim: usize) -> Result<(), CubaError> {
+        match self.xgiven.iter().find(|point| point.len() != ndim) {
+            Some(point) => Err(CubaError::BadXGiven(point.len(), ndim)),
+            None => Ok(()),
+        }
+    }
+
+    /// Runs Divonne on `integrand`, through the callback `callback`, which
+    /// is called with a `DivonneUserData` and batches of up to `nvec` points.
+    unsafe fn run<'a, T>(&mut self, ndim: usize, ncomp: usize, integrand: T,
+                         callback: bindings::integrand_t, nvec: usize,
+                         epsrel: Real, epsabs: Real) -> Result<CubaIntegrationResults, CubaError>
+        where T: DivonneIntegrand<'a>
+    {
+        // Using cuba's parallelization via fork() would deeply break Rust's
+        // concurrency model and safety guarantees. So, we'll turn it off.
+        bindings::cubacores(0, 0);
+
+        let statefile = statefile_cstring(self.statefile.as_ref())?;
+        let mut xgiven = vec![0.0; self.xgiven.len() * ndim];
+        for (point, out) in self.xgiven.iter().zip(xgiven.chunks_mut(ndim)) {
+            match self.domain {
+                Some(ref domain) => domain.inverse_transform(&point[..], out),
+                None => out.copy_from_slice(&point[..]),
+            }
+        }
+
+        let mut nregions = 0;
+        let mut neval = 0;
+        let mut fail = 0;
+        let (mut value, mut error, mut prob) =
+                (vec![0.0; ncomp], vec![0.0; ncomp], vec![0.0; ncomp]);
+
+        let (nextra, peakfinder) = match self.peakfinder {
+            Some(_) => (self.nextra,
+                        Some(divonne_peakfinder::<T> as unsafe extern "C" fn(_, _, _, _, _))),
+            None => (0, None),
+        };
+
+        let mut data = DivonneUserData {
+            integrand,
+            peakfinder: self.peakfinder.as_mut(),
+        };
+        bindings::llDivonne(ndim as c_int, ncomp as c_int,
+                            callback, mem::transmute(&mut data),
+                            nvec as c_longlong,
+                            epsrel,
+                            epsabs,
+                            self.flags.bits(),
+                            self.seed as c_int,
+                            self.mineval as c_longlong,
+                            self.maxeval as c_longlong,
+                            self.key1,
+                            self.key2,
+                            self.key3,
+                            self.maxpass as c_int,
+                            self.border,
+                            self.maxchisq,
+                            self.mindeviation,
+                            self.xgiven.len() as c_longlong,
+                            ndim as c_int /* ldxgiven */,
+                            if xgiven.is_empty() {
+                                ptr::null_mut()
+                            } else {
+                                xgiven.as_mut_ptr()
+                            },
+                            nextra as c_longlong,
+                            peakfinder,
+                            statefile.as_ref().map(|path| path.as_ptr()).unwrap_or(ptr::null()),
+                            // spin
+                            ptr::null_mut(),
+                            &mut nregions,
+                            &mut neval,
+                            &mut fail,
+                            value.as_mut_ptr(),
+                            error.as_mut_ptr(),
+                            prob.as_mut_ptr());
+        data.integrand.maybe_resume_unwind();
+
+        if fail == 0 {
+            Ok(CubaIntegrationResults {
+                nregions: Some(nregions), neval,
+                results: value.iter().zip(error.iter()).zip(prob.iter())
+                              .map(|((&value, &error), &prob)|
+                                     CubaIntegrationResult {
+                                         value, error, prob
+                                     })
+                              .collect()
+            })
+        } else if fail == -1 {
+            // `baddim`
+            Err(CubaError::BadDim("divonne", ndim))
+        } else if fail == -2 {
+            // `badcomp`
+            Err(CubaError::BadComp("divonne", ncomp))
+        } else if fail >= 1 {
+            // For Divonne, `fail` is an estimate of the number of additional
+            // integrand evaluations needed to reach the desired accuracy.
+            Err(CubaError::DidNotConverge(CubaIntegrationResults {
+                nregions: Some(nregions), neval,
+                results: value.iter().zip(error.iter()).zip(prob.iter())
+                              .map(|((&value, &error), &prob)|
+                                     CubaIntegrationResult {
+                                         value, error, prob
+                                     })
+                              .collect()
+            }))
+        } else {
+            unreachable!("Divonne returned invalid failure code: {}", fail)
+        }
+    }
+}
+
+/// The integrand of a Divonne run, along with what its peakfinder needs:
+/// the integration domain, and to catch panics alongside the integrand.
+trait DivonneIntegrand<'a> {
+    fn domain(&self) -> Option<&'a Hyperrectangle>;
+    /// Runs `g`, returning `None` if it (or the integrand) has panicked.
+    fn try_run<R, G: FnOnce() -> R>(&mut self, g: G) -> Option<R>;
+    fn maybe_resume_unwind(self);
+}
+
+impl<'a, A, B, F> DivonneIntegrand<'a> for CubaIntegrand<'a, A, B, F>
+    where A: IntegrandInput,
+          B: IntegrandOutput,
+          F: FnMut(A) -> B
+{
+    fn domain(&self) -> Option<&'a Hyperrectangle> {
+        self.domain
+    }
+
+    fn try_run<R, G: FnOnce() -> R>(&mut self, g: G) -> Option<R> {
+        self.lp.try_run(g).ok()
+    }
+
+    fn maybe_resume_unwind(self) {
+        CubaIntegrand::maybe_resume_unwind(self)
+    }
+}
+
+impl<'a, I: BatchIntegrand> DivonneIntegrand<'a> for CubaBatchIntegrand<'a, I> {
+    fn domain(&self) -> Option<&'a Hyperrectangle> {
+        self.domain
+    }
+
+    fn try_run<R, G: FnOnce() -> R>(&mut self, g: G) -> Option<R> {
+        if self.err.is_some() {
+            return None
+        }
+        match panic::catch_unwind(panic::AssertUnwindSafe(g)) {
+            Ok(res) => Some(res),
+            Err(err) => {
+                self.err = Some(err);
+                None
+            }
+        }
+    }
+
+    fn maybe_resume_unwind(self) {
+        CubaBatchIntegrand::maybe_resume_unwind(self)
+    }
+}
+
+struct DivonneUserData<'a, T> {
+    integrand: T,
+    peakfinder: Option<&'a mut Box<FnMut(&[(Real, Real)]) -> Vec<Vec<Real>>>>,
+}
+
+unsafe extern "C"
+fn divonne_integrand<A, B, F>(ndim: *const c_int,
+                              x: *const Real,
+                              ncomp: *const c_int,
+                              f: *mut Real,
+                              userdata: *mut c_void) -> c_int
+    where A: IntegrandInput,
+          B: IntegrandOutput,
+          F: FnMut(A) -> B
+{
+    let dataptr = userdata as *mut DivonneUserData<CubaIntegrand<A, B, F>>;
+    let data: &mut DivonneUserData<CubaIntegrand<A, B, F>> = &mut *dataptr;
+
+    let args = slice::from_raw_parts(x, *ndim as usize);
+    let output = slice::from_raw_parts_mut(f, *ncomp as usize);
+
+    data.integrand.call(args, output)
+}
+
+unsafe extern "C"
+fn divonne_parallel_integrand<A, B, F>(ndim: *const c_int,
+                                       x: *const Real,
+                                       ncomp: *const c_int,
+                                       f: *mut Real,
+                                       userdata: *mut c_void,
+                                       nvec: *const c_longlong,
+                                       _core: *const c_int) -> c_int
+    where A: IntegrandInput,
+          B: IntegrandOutput,
+          F: Fn(A) -> B + Sync
+{
+    let dataptr = userdata as *mut DivonneUserData<CubaIntegrand<A, B, F>>;
+    let data: &mut DivonneUserData<CubaIntegrand<A, B, F>> = &mut *dataptr;
+
+    let nvec = match batch_len(nvec) {
+        Some(nvec) => nvec,
+        None => return -999,
+    };
+    let args = slice::from_raw_parts(x, nvec * *ndim as usize);
+    let output = slice::from_raw_parts_mut(f, nvec * *ncomp as usize);
+
+    data.integrand.call_parallel(args, output)
+}
+
+unsafe extern "C"
+fn divonne_batch_integrand<I>(ndim: *const c_int,
+                              x: *const Real,
+                              ncomp: *const c_int,
+                              f: *mut Real,
+                              userdata: *mut c_void,
+                              nvec: *const c_longlong,
+                              _core: *const c_int) -> c_int
+    where I: BatchIntegrand
+{
+    let dataptr = userdata as *mut DivonneUserData<CubaBatchIntegrand<I>>;
+    let data: &mut DivonneUserData<CubaBatchIntegrand<I>> = &mut *dataptr;
+
+    let nvec = match batch_len(nvec) {
+        Some(nvec) => nvec,
+        None => return -999,
+    };
+    let args = slice::from_raw_parts(x, nvec * *ndim as usize);
+    let output = slice::from_raw_parts_mut(f, nvec * *ncomp as usize);
+
+    data.integrand.call(args, output)
+}
+
+unsafe extern "C"
+fn divonne_peakfinder<'a, T>(ndim: *const c_int,
+                             b: *const Real,
+                             n: *mut c_int,
+                             x: *mut Real,
+                             userdata: *mut c_void)
+    where T: DivonneIntegrand<'a>
+{
+    let dataptr = userdata as *mut DivonneUserData<T>;
+    let data: &mut DivonneUserData<T> = &mut *dataptr;
+
+    let ndim = *ndim as usize;
+    let nmax = *n as usize;
+    // Cuba stores the bounds as (lower, upper) pairs for each dimension
+    let (mut lower, mut upper): (Vec<Real>, Vec<Real>) =
+            slice::from_raw_parts(b, 2 * ndim)
+                 .chunks(2)
+                 .map(|bound| (bound[0], bound[1]))
+                 .unzip();
+    let points = slice::from_raw_parts_mut(x, nmax * ndim);
+
+    let DivonneUserData { ref mut integrand, ref mut peakfinder } = *data;
+    let domain = integrand.domain();
+    if let Some(domain) = domain {
+        let (unit_lower, unit_upper) = (lower.clone(), upper.clone());
+        domain.transform(&unit_lower[..], &mut lower[..]);
+        domain.transform(&unit_upper[..], &mut upper[..]);
+    }
+    let bounds = lower.into_iter().zip(upper.into_iter()).collect::<Vec<_>>();
+
+    let found = match peakfinder {
+        &mut Some(ref mut peakfinder) => integrand.try_run(|| {
+            let found = peakfinder(&bounds[..]);
+            let count = cmp::min(found.len(), nmax);
+            for (point, out) in found.iter().take(count).zip(points.chunks_mut(ndim)) {
+                if point.len() != ndim {
+                    panic!("Peakfinder returned point of wrong dimension: expected {}, got {}",
+                           ndim, point.len());
+                }
+                match domain {
+                    Some(domain) => domain.inverse_transform(&point[..], out),
+                    None => out.copy_from_slice(&point[..]),
+                }
+            }
+            count
+        }),
+        &mut None => Some(0),
+    };
+
+    // If the peakfinder panicked, report no points - the next integrand call
+    // will abort the integration.
+    *n = found.unwrap_or(0) as c_int;
+}
+
+impl Integrator for Divonne {
+    type Success = CubaIntegrationResults;
+    type Failure = super::CubaError;
+    fn integrate<A, B, F: FnMut(A) -> B>(&mut self, mut fun: F, epsrel: Real, epsabs: Real) -> Result<Self::Success, Self::Failure>
+        where A: IntegrandInput,
+              B: IntegrandOutput
+    {
+        let domain = self.domain.clone();
+        let (ndim, ncomp) = integrand_dims("divonne", &mut fun, domain.as_ref())?;
+        self.check_xgiven(ndim)?;
+
+        let integrand = CubaIntegrand::new(fun, domain.as_ref());
+        unsafe {
+            self.run(ndim, ncomp, integrand, Some(divonne_integrand::<A, B, F>),
+                     1, epsrel, epsabs)
+        }
+    }
+}
+
+impl BatchIntegrator for Divonne {
+    type Success = CubaIntegrationResults;
+    type Failure = super::CubaError;
+    fn integrate_batch<I>(&mut self, integrand: I, epsrel: Real, epsabs: Real) -> Result<Self::Success, Self::Failure>
+        where I: BatchIntegrand
+    {
+        let domain = self.domain.clone();
+        let (ndim, ncomp) = batch_dims("divonne", &integrand, domain.as_ref())?;
+        self.check_xgiven(ndim)?;
+
+        let integrand = CubaBatchIntegrand::new(integrand, domain.as_ref());
+        let nvec = self.nvec;
+        unsafe {
+            self.run(ndim, ncomp, integrand, batch_integrand(divonne_batch_integrand::<I>),
+                     nvec, epsrel, epsabs)
+        }
+    }
+}
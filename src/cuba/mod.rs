@@ -15,7 +15,7 @@
 //! ```
 //! use std::f64::consts::PI;
 //! use integrators::{Integrator, Real, Real3};
-//! use integrators::cuba::{Cuhre, IntegrationRange};
+//! use integrators::cuba::{Cuhre, Hyperrectangle};
 //!
 //! // An integral over a sphere is:
 //! // \int f(\theta, \phi, r) r^2 sin(\theta) d\theta d\phi dr
@@ -23,19 +23,16 @@
 //! // and \phi from 0 to 2pi
 //!
 //! fn volume(radius: Real) -> Real {
-//!     let r_range = IntegrationRange::new(0.0, radius);
-//!     let theta_range = IntegrationRange::new(0.0, PI);
-//!     let phi_range = IntegrationRange::new(0.0, 2.0*PI);
+//!     // Cuba integrates over the unit hypercube, so we give it the
+//!     // integration domain, which takes care of the Jacobian.
+//!     let domain = Hyperrectangle::new(vec![(0.0, radius),
+//!                                           (0.0, PI),
+//!                                           (0.0, 2.0*PI)]);
 //!
 //!     let results = Cuhre::new(999999)
-//!                         .integrate(|(rs, thetas, phis): Real3| {
-//!                             let r = r_range.transform(rs);
-//!                             let theta = theta_range.transform(thetas);
-//!                             let phi = phi_range.transform(phis);
+//!                         .with_domain(domain)
+//!                         .integrate(|(r, theta, _phi): Real3| {
 //!                             r * r * theta.sin()
-//!                             * r_range.jacobian()
-//!                             * theta_range.jacobian()
-//!                             * phi_range.jacobian()
 //!                         }, 1e-5, 1e-18).unwrap();
 //!
 //!     assert_eq!(results.results.len(), 1);
@@ -61,7 +58,8 @@
 //! of different lengths at different integrand calls will trigger a panic.
 //!
 //! This example shows how to do so by solving for the volume of a number
-//! of spheres simultaneously, using the same methods as above.
+//! of spheres simultaneously. Since the spheres have different radii, their
+//! integration ranges are transformed by hand, with `IntegrationRange`.
 //!
 //! ```
 //! use std::f64::consts::PI;
@@ -109,23 +107,229 @@
 //!     assert!((calc - ex).abs() < ex*1e-5);
 //! }
 //! ```
+//!
+//! # Parallel Integration
+//!
+//! Cuba's own parallelization forks worker processes, which doesn't mix with
+//! Rust's safety guarantees, so it is always turned off. Instead, each
+//! integrator has an `integrate_parallel` method for integrands which are
+//! `Fn + Sync`: Cuba passes them batches of points (see `with_nvec`), which
+//! are split between Rust threads (see `with_threads`).
+//!
+//! ```
+//! use integrators::Real;
+//! use integrators::cuba::Vegas;
+//!
+//! let res = Vegas::new().with_maxeval(1000000)
+//!                       .with_threads(4)
+//!                       .integrate_parallel(|(x, y): (Real, Real)| x * y, 1e-3, 1e-12)
+//!                       .unwrap();
+//! assert!((res.results[0].value - 0.25).abs() < 1e-2);
+//! ```
 
-use std::{error, fmt, slice, vec};
-use std::convert::From;
+use std::{error, fmt, mem, panic, slice, thread, vec};
+use std::any::Any;
+use std::convert::{From, TryFrom};
+use std::ffi::CString;
+use std::f64::INFINITY;
+use std::f64::consts::PI;
 use std::os::raw::{c_int, c_longlong, c_void};
+use std::path::PathBuf;
 
-use super::traits::{IntegrandInput, IntegrandOutput};
+use super::bindings;
+use super::traits::{BatchIntegrand, IntegrandInput, IntegrandOutput};
 use super::{IntegrationResult, Real};
-use super::ffi::LandingPad;
+use super::ffi::{LandingPad, Workers};
 
 mod cuhre;
 pub use self::cuhre::Cuhre;
 
+mod divonne;
+pub use self::divonne::Divonne;
+
 mod suave;
 pub use self::suave::Suave;
 
 mod vegas;
-pub use self::vegas::Vegas;
+pub use self::vegas::{Vegas, MAX_GRID_SLOTS};
+
+/// The integrand, as seen by Cuba: maps points in Cuba's unit hypercube to
+/// the integration domain, if one is given, before calling the integrand,
+/// and scales its output by the Jacobian of that mapping.
+struct CubaIntegrand<'a, A, B, F: FnMut(A) -> B> {
+    lp: LandingPad<A, B, F>,
+    domain: Option<&'a Hyperrectangle>,
+    args: Vec<Real>,
+    workers: Workers,
+}
+
+impl<'a, A, B, F> CubaIntegrand<'a, A, B, F>
+    where A: IntegrandInput,
+          B: IntegrandOutput,
+          F: FnMut(A) -> B
+{
+    fn new(fun: F, domain: Option<&'a Hyperrectangle>) -> Self {
+        CubaIntegrand {
+            lp: LandingPad::new(fun),
+            domain,
+            args: vec![0.0; A::input_size()],
+            workers: Workers::new(1),
+        }
+    }
+
+    /// Split batches of points between `nthreads` threads, in `call_parallel`.
+    /// The threads are started here, and kept until the integrand is dropped.
+    fn with_threads(self, nthreads: usize) -> Self {
+        CubaIntegrand { workers: Workers::new(nthreads), ..self }
+    }
+
+    /// Calls the integrand at the point `x` of the unit hypercube, and
+    /// returns the status code Cuba expects.
+    fn call(&mut self, x: &[Real], output: &mut [Real]) -> c_int {
+        let res = match self.domain {
+            Some(domain) => {
+                let jacobian = domain.jacobian_at(x);
+                if !jacobian.is_finite() {
+                    // A point at infinity, where the integrand must vanish.
+                    // (The Jacobian is NaN if a range also has zero length.)
+                    for out in output.iter_mut() {
+                        *out = 0.0;
+                    }
+                    Ok(())
+                } else {
+                    domain.transform(x, &mut self.args[..]);
+                    self.lp.try_call(&self.args[..], output).map(|_| {
+                        for out in output.iter_mut() {
+                            *out *= jacobian;
+                        }
+                    })
+                }
+            },
+            None => self.lp.try_call(x, output),
+        };
+        match res {
+            Ok(_) => 0,
+            // -999 is special `abort` code to Cuba
+            Err(_) => -999,
+        }
+    }
+
+    fn maybe_resume_unwind(self) {
+        self.lp.maybe_resume_unwind()
+    }
+}
+
+impl<'a, A, B, F> CubaIntegrand<'a, A, B, F>
+    where A: IntegrandInput,
+          B: IntegrandOutput,
+          F: Fn(A) -> B + Sync
+{
+    /// Calls the integrand at each of the points of the unit hypercube in
+    /// `x`, in parallel, and returns the status code Cuba expects.
+    fn call_parallel(&mut self, x: &[Real], output: &mut [Real]) -> c_int {
+        let CubaIntegrand { ref mut lp, domain, ref workers, .. } = *self;
+        let res = evaluate_batch(domain, A::input_size(), x, output, |args, output| {
+            lp.try_call_parallel(args, output, workers).map_err(|_| ())
+        });
+        match res {
+            Ok(_) => 0,
+            // -999 is special `abort` code to Cuba
+            Err(_) => -999,
+        }
+    }
+}
+
+/// A `BatchIntegrand`, as seen by Cuba: like `CubaIntegrand`, but evaluating
+/// whole batches of points, and catching any panic while doing so.
+struct CubaBatchIntegrand<'a, I> {
+    integrand: I,
+    domain: Option<&'a Hyperrectangle>,
+    err: Option<Box<Any + Send + 'static>>,
+}
+
+impl<'a, I: BatchIntegrand> CubaBatchIntegrand<'a, I> {
+    fn new(integrand: I, domain: Option<&'a Hyperrectangle>) -> Self {
+        CubaBatchIntegrand {
+            integrand, domain, err: None
+        }
+    }
+
+    /// Evaluates the integrand at each of the points of the unit hypercube
+    /// in `x`, and returns the status code Cuba expects.
+    fn call(&mut self, x: &[Real], output: &mut [Real]) -> c_int {
+        if self.err.is_some() {
+            return -999
+        }
+
+        let ndim = self.integrand.input_size();
+        let CubaBatchIntegrand { ref mut integrand, domain, ref mut err } = *self;
+        let res = evaluate_batch(domain, ndim, x, output, |args, output| {
+            if args.is_empty() {
+                return Ok(())
+            }
+            panic::catch_unwind(panic::AssertUnwindSafe(|| integrand.evaluate(args, output)))
+                  .map_err(|e| *err = Some(e))
+        });
+        match res {
+            Ok(_) => 0,
+            // -999 is special `abort` code to Cuba
+            Err(_) => -999,
+        }
+    }
+
+    fn maybe_resume_unwind(self) {
+        if let Some(err) = self.err {
+            panic::resume_unwind(err)
+        }
+    }
+}
+
+/// Calls `eval` on the points of the unit hypercube in `x`, each with `ndim`
+/// coordinates, mapped to `domain` if one is given, and scales the outputs
+/// by the Jacobian of that mapping. Points at infinity, where the integrand
+/// must vanish, aren't evaluated.
+fn evaluate_batch<G>(domain: Option<&Hyperrectangle>, ndim: usize,
+                     x: &[Real], output: &mut [Real], mut eval: G) -> Result<(), ()>
+    where G: FnMut(&[Real], &mut [Real]) -> Result<(), ()>
+{
+    let domain = match domain {
+        Some(domain) => domain,
+        None => return eval(x, output),
+    };
+
+    let npoints = x.len() / ndim;
+    let ncomp = output.len() / npoints;
+
+    let mut jacobians = Vec::with_capacity(npoints);
+    let mut args = Vec::with_capacity(x.len());
+    for point in x.chunks(ndim) {
+        let jacobian = domain.jacobian_at(point);
+        if jacobian.is_finite() {
+            let start = args.len();
+            args.resize(start + ndim, 0.0);
+            domain.transform(point, &mut args[start..]);
+        }
+        jacobians.push(jacobian);
+    }
+
+    let mut values = vec![0.0; args.len() / ndim * ncomp];
+    eval(&args[..], &mut values[..])?;
+
+    let mut values = values.chunks(ncomp);
+    for (&jacobian, out) in jacobians.iter().zip(output.chunks_mut(ncomp)) {
+        if !jacobian.is_finite() {
+            for out in out.iter_mut() {
+                *out = 0.0;
+            }
+        } else {
+            let value = values.next().expect("a value for each finite point");
+            for (out, &value) in out.iter_mut().zip(value.iter()) {
+                *out = value * jacobian;
+            }
+        }
+    }
+    Ok(())
+}
 
 unsafe extern "C"
 fn cuba_integrand<A, B, F>(ndim: *const c_int,
@@ -137,33 +341,218 @@ fn cuba_integrand<A, B, F>(ndim: *const c_int,
           B: IntegrandOutput,
           F: FnMut(A) -> B
 {
-    let fnptr = userdata as *mut LandingPad<A, B, F>;
-    let lp: &mut LandingPad<A, B, F> = &mut *fnptr;
+    let fnptr = userdata as *mut CubaIntegrand<A, B, F>;
+    let integrand: &mut CubaIntegrand<A, B, F> = &mut *fnptr;
 
     let args = slice::from_raw_parts(x, *ndim as usize);
     let output = slice::from_raw_parts_mut(f, *ncomp as usize);
 
-    match lp.try_call(args, output) {
-        Ok(_) => 0,
-        // -999 is special `abort` code to Cuba
-        Err(_) => -999,
+    integrand.call(args, output)
+}
+
+/// Converts the path of a state file to the C string Cuba expects.
+fn statefile_cstring(statefile: Option<&PathBuf>) -> Result<Option<CString>, CubaError> {
+    match statefile {
+        Some(path) => path.to_str()
+                          .and_then(|path| CString::new(path).ok())
+                          .map(Some)
+                          .ok_or_else(|| CubaError::BadStatefile(path.clone())),
+        None => Ok(None),
+    }
+}
+
+/// The number of points Cuba passes to `integrate_parallel` and
+/// `integrate_batch` integrands at once, unless set with `with_nvec`.
+const DEFAULT_NVEC: usize = 1024;
+
+/// An integrand which takes the two arguments Cuba passes after `userdata`,
+/// but which aren't declared in `cuba.h`: the number of points in the batch,
+/// and the core number (which is always 0, as we don't use Cuba's
+/// parallelization).
+type BatchIntegrandFn = unsafe extern "C" fn(*const c_int, *const Real, *const c_int, *mut Real,
+                                             *mut c_void, *const c_longlong, *const c_int) -> c_int;
+
+/// Casts a batch integrand to the integrand type declared by Cuba. Since C
+/// callers may pass more arguments than the callee uses, Cuba always passes
+/// `nvec` and `core`, and this is how Cuba's own examples declare batch
+/// integrands.
+fn batch_integrand(fun: BatchIntegrandFn) -> bindings::integrand_t {
+    unsafe { Some(mem::transmute(fun)) }
+}
+
+/// The number of points in a batch, which the `ll` entry points of Cuba
+/// pass as a `long long`. `None` if it doesn't fit in a `usize`.
+unsafe fn batch_len(nvec: *const c_longlong) -> Option<usize> {
+    usize::try_from(*nvec).ok()
+}
+
+unsafe extern "C"
+fn cuba_parallel_integrand<A, B, F>(ndim: *const c_int,
+                                    x: *const Real,
+                                    ncomp: *const c_int,
+                                    f: *mut Real,
+                                    userdata: *mut c_void,
+                                    nvec: *const c_longlong,
+                                    _core: *const c_int) -> c_int
+    where A: IntegrandInput,
+          B: IntegrandOutput,
+          F: Fn(A) -> B + Sync
+{
+    let fnptr = userdata as *mut CubaIntegrand<A, B, F>;
+    let integrand: &mut CubaIntegrand<A, B, F> = &mut *fnptr;
+
+    let nvec = match batch_len(nvec) {
+        Some(nvec) => nvec,
+        None => return -999,
+    };
+    let args = slice::from_raw_parts(x, nvec * *ndim as usize);
+    let output = slice::from_raw_parts_mut(f, nvec * *ncomp as usize);
+
+    integrand.call_parallel(args, output)
+}
+
+unsafe extern "C"
+fn cuba_batch_integrand<I>(ndim: *const c_int,
+                           x: *const Real,
+                           ncomp: *const c_int,
+                           f: *mut Real,
+                           userdata: *mut c_void,
+                           nvec: *const c_longlong,
+                           _core: *const c_int) -> c_int
+    where I: BatchIntegrand
+{
+    let fnptr = userdata as *mut CubaBatchIntegrand<I>;
+    let integrand: &mut CubaBatchIntegrand<I> = &mut *fnptr;
+
+    let nvec = match batch_len(nvec) {
+        Some(nvec) => nvec,
+        None => return -999,
+    };
+    let args = slice::from_raw_parts(x, nvec * *ndim as usize);
+    let output = slice::from_raw_parts_mut(f, nvec * *ncomp as usize);
+
+    integrand.call(args, output)
+}
+
+/// The number of threads to evaluate the integrand on: `threads` if given,
+/// and otherwise the available parallelism of the machine.
+fn thread_count(threads: Option<usize>) -> usize {
+    threads.unwrap_or_else(|| thread::available_parallelism().map(|n| n.get()).unwrap_or(1))
+}
+
+/// Finds the number of inputs and outputs of the integrand, evaluating it at
+/// the center of the integration domain. Fails if the domain doesn't have as
+/// many dimensions as the integrand has inputs.
+fn integrand_dims<A, B, F>(algorithm: &'static str, fun: &mut F, domain: Option<&Hyperrectangle>)
+        -> Result<(usize, usize), CubaError>
+    where A: IntegrandInput,
+          B: IntegrandOutput,
+          F: FnMut(A) -> B
+{
+    let inputs = A::input_size();
+    let mut center = vec![0.5; inputs];
+    if let Some(domain) = domain {
+        if domain.ndim() != inputs {
+            return Err(CubaError::BadDim(algorithm, inputs))
+        }
+        let unit = center.clone();
+        domain.transform(&unit[..], &mut center[..]);
+    }
+    let outputs = fun(A::from_args(&center[..])).output_size();
+    Ok((inputs, outputs))
+}
+
+/// Finds the number of inputs and outputs of a `BatchIntegrand`, and checks
+/// them against the integration domain, as with `integrand_dims`.
+fn batch_dims<I>(algorithm: &'static str, integrand: &I, domain: Option<&Hyperrectangle>)
+        -> Result<(usize, usize), CubaError>
+    where I: BatchIntegrand
+{
+    let inputs = integrand.input_size();
+    if domain.map(|domain| domain.ndim() != inputs).unwrap_or(false) {
+        return Err(CubaError::BadDim(algorithm, inputs))
     }
+    Ok((inputs, integrand.output_size()))
+}
+
+/// The kinds of `IntegrationRange`, each with its own substitution from the
+/// unit interval.
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum RangeKind {
+    /// [`start`, `start + length`]: `y = start + x length`
+    Finite { start: Real, length: Real },
+    /// [`start`, +inf): `y = start + x / (1 - x)`
+    ToInfinity { start: Real },
+    /// (-inf, `end`]: `y = end - (1 - x) / x`
+    FromNegInfinity { end: Real },
+    /// (-inf, +inf): `y = tan(pi (x - 1/2))`
+    Infinite,
 }
 
 /// Since Cuba integrates on the unit hypercube, it is convenient to have a
 /// helper to convert into a different integration range.
-#[derive(Debug, PartialEq)]
+///
+/// Ranges may be finite, semi-infinite, or infinite. Infinite ranges are
+/// mapped onto the unit interval with a non-linear substitution, so their
+/// Jacobian depends on the point; see `jacobian_at`. The integrand should
+/// decay fast enough for the transformed integrand to be well-behaved near
+/// the edges of the unit interval, e.g. faster than `1 / y^2` for large `y`.
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub struct IntegrationRange {
-    start: Real,
-    length: Real,
+    kind: RangeKind,
 }
 
 impl IntegrationRange {
-    /// Create a new range from start to end.
+    /// Create a new range from start to end. Either bound may be infinite:
+    /// `new(a, INFINITY)` is the same as `to_infinity(a)`, and likewise for
+    /// `from_neg_infinity` and `infinite`.
+    ///
+    /// # Panics
+    /// If an infinite range is given in descending order, e.g. from +inf to
+    /// `a`, or if either bound is NaN, panics.
     pub fn new(start: Real, end: Real) -> Self {
+        assert!(!start.is_nan() && !end.is_nan(), "integration bounds can't be NaN");
+        match (start.is_infinite(), end.is_infinite()) {
+            (false, false) => IntegrationRange {
+                kind: RangeKind::Finite { start, length: end - start }
+            },
+            (false, true) if end > 0.0 => IntegrationRange::to_infinity(start),
+            (true, false) if start < 0.0 => IntegrationRange::from_neg_infinity(end),
+            (true, true) if start < end => IntegrationRange::infinite(),
+            _ => panic!("infinite integration ranges must be in ascending order: got {} to {}",
+                        start, end)
+        }
+    }
+
+    /// Create a new range from `start` to +infinity, using the substitution
+    /// `y = start + x / (1 - x)`.
+    pub fn to_infinity(start: Real) -> Self {
+        IntegrationRange {
+            kind: RangeKind::ToInfinity { start }
+        }
+    }
+
+    /// Create a new range from -infinity to `end`, using the substitution
+    /// `y = end - (1 - x) / x`.
+    pub fn from_neg_infinity(end: Real) -> Self {
+        IntegrationRange {
+            kind: RangeKind::FromNegInfinity { end }
+        }
+    }
+
+    /// Create a new range over the whole real line, using the substitution
+    /// `y = tan(pi (x - 1/2))`.
+    pub fn infinite() -> Self {
         IntegrationRange {
-            start,
-            length: end - start
+            kind: RangeKind::Infinite
+        }
+    }
+
+    /// Whether both bounds of the range are finite.
+    pub fn is_finite(&self) -> bool {
+        match self.kind {
+            RangeKind::Finite { .. } => true,
+            _ => false,
         }
     }
 
@@ -174,21 +563,333 @@ impl IntegrationRange {
     /// If x is not between 0 and 1 (inclusive), panics.
     pub fn transform(&self, x: Real) -> Real {
         assert!((x >= 0.0) & (x <= 1.0));
-        self.start + x * self.length
+        match self.kind {
+            RangeKind::Finite { start, length } => start + x * length,
+            RangeKind::ToInfinity { start } => start + x / (1.0 - x),
+            RangeKind::FromNegInfinity { end } => end - (1.0 - x) / x,
+            RangeKind::Infinite => (PI * (x - 0.5)).tan(),
+        }
+    }
+
+    /// Scale factor of a finite range. Infinite for a range which is not
+    /// finite, whose Jacobian depends on the point; see `jacobian_at`.
+    pub fn jacobian(&self) -> Real {
+        match self.kind {
+            RangeKind::Finite { length, .. } => length,
+            _ => INFINITY,
+        }
+    }
+
+    /// The Jacobian of `transform` at the point `x`, i.e. `dy/dx`. This is
+    /// infinite at the edges of the unit interval which are mapped to
+    /// infinity.
+    ///
+    /// # Panics
+    /// If x is not between 0 and 1 (inclusive), panics.
+    pub fn jacobian_at(&self, x: Real) -> Real {
+        assert!((x >= 0.0) & (x <= 1.0));
+        match self.kind {
+            RangeKind::Finite { length, .. } => length,
+            RangeKind::ToInfinity { .. } => (1.0 - x).powi(-2),
+            RangeKind::FromNegInfinity { .. } => x.powi(-2),
+            RangeKind::Infinite => PI / (PI * (x - 0.5)).cos().powi(2),
+        }
+    }
+
+    /// The inverse of `transform`, i.e. converts from [`start`, `end`] to
+    /// [0,1].
+    pub fn inverse_transform(&self, y: Real) -> Real {
+        match self.kind {
+            RangeKind::Finite { start, length } => (y - start) / length,
+            RangeKind::ToInfinity { start } => (y - start) / (1.0 + y - start),
+            RangeKind::FromNegInfinity { end } => 1.0 / (1.0 + end - y),
+            RangeKind::Infinite => y.atan() / PI + 0.5,
+        }
+    }
+}
+
+/// An integration domain which is a product of one `IntegrationRange` for
+/// each dimension. Given to a Cuba integrator with `with_domain`, the
+/// integrand is called with arguments in this domain, rather than Cuba's
+/// unit hypercube, and the Jacobian is applied automatically.
+///
+/// Each dimension may be finite, semi-infinite, or infinite. Points of the
+/// unit hypercube which are mapped to infinity contribute nothing to the
+/// integral, and the integrand is not called there.
+///
+/// ```
+/// use integrators::{Integrator, Real};
+/// use integrators::cuba::{Cuhre, Hyperrectangle};
+///
+/// // \int_0^2 \int_1^4 x y dy dx = 2 * 7.5
+/// let domain = Hyperrectangle::new(vec![(0.0, 2.0), (1.0, 4.0)]);
+/// let res = Cuhre::new(100000)
+///                 .with_domain(domain)
+///                 .integrate(|(x, y): (Real, Real)| x * y, 1e-8, 1e-12)
+///                 .unwrap();
+/// assert!((res.results[0].value - 15.0).abs() < 1e-6);
+///
+/// // \int_0^\infty \int_{-\infty}^\infty exp(-x - y^2) dy dx = sqrt(pi)
+/// let domain = Hyperrectangle::new(vec![(0.0, ::std::f64::INFINITY),
+///                                       (-::std::f64::INFINITY, ::std::f64::INFINITY)]);
+/// let res = Cuhre::new(1000000)
+///                 .with_domain(domain)
+///                 .integrate(|(x, y): (Real, Real)| (-x - y * y).exp(), 1e-6, 1e-12)
+///                 .unwrap();
+/// assert!((res.results[0].value - ::std::f64::consts::PI.sqrt()).abs() < 1e-5);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hyperrectangle {
+    ranges: Vec<IntegrationRange>,
+}
+
+impl Hyperrectangle {
+    /// Creates a new domain from the `(lower, upper)` bounds of each
+    /// dimension. Either bound may be infinite; see `IntegrationRange::new`.
+    ///
+    /// # Panics
+    /// If an infinite range is given in descending order, panics.
+    pub fn new<I>(bounds: I) -> Self
+        where I: IntoIterator<Item=(Real, Real)> {
+        Hyperrectangle {
+            ranges: bounds.into_iter()
+                          .map(|(start, end)| IntegrationRange::new(start, end))
+                          .collect()
+        }
+    }
+
+    /// Creates a new domain from the range of each dimension.
+    pub fn from_ranges<I>(ranges: I) -> Self
+        where I: IntoIterator<Item=IntegrationRange> {
+        Hyperrectangle {
+            ranges: ranges.into_iter().collect()
+        }
+    }
+
+    /// The number of dimensions of the domain.
+    pub fn ndim(&self) -> usize {
+        self.ranges.len()
     }
 
-    /// Scale factor.
+    pub fn ranges(&self) -> &[IntegrationRange] {
+        &self.ranges[..]
+    }
+
+    /// The volume of a finite domain, which is the Jacobian of the
+    /// transformation from the unit hypercube. If any range of the domain is
+    /// not finite, this is infinite, unless another range has zero length,
+    /// in which case the volume is zero; see `jacobian_at` for the Jacobian
+    /// at a point.
     pub fn jacobian(&self) -> Real {
-        self.length
+        let finite = self.ranges.iter()
+                         .filter(|r| r.is_finite())
+                         .map(|r| r.jacobian())
+                         .product::<Real>();
+        if finite != 0.0 && self.ranges.iter().any(|r| !r.is_finite()) {
+            INFINITY
+        } else {
+            finite
+        }
+    }
+
+    /// The Jacobian of the transformation from the unit hypercube, at the
+    /// point `x` of the unit hypercube.
+    ///
+    /// # Panics
+    /// If `x` does not have one coordinate for each dimension, or is not in
+    /// the unit hypercube, panics.
+    pub fn jacobian_at(&self, x: &[Real]) -> Real {
+        assert!(x.len() == self.ndim());
+        self.ranges.iter().zip(x.iter())
+            .map(|(r, &x)| r.jacobian_at(x))
+            .product()
+    }
+
+    /// Converts the point `x` of the unit hypercube into `out`, the
+    /// corresponding point in this domain.
+    ///
+    /// # Panics
+    /// If `x` or `out` do not have one coordinate for each dimension, or if
+    /// `x` is not in the unit hypercube, panics.
+    pub fn transform(&self, x: &[Real], out: &mut [Real]) {
+        assert!(x.len() == self.ndim() && out.len() == self.ndim());
+        for ((range, &x), out) in self.ranges.iter().zip(x.iter()).zip(out.iter_mut()) {
+            *out = range.transform(x);
+        }
+    }
+
+    /// The inverse of `transform`, converting the point `y` of this domain
+    /// into `out`, the corresponding point in the unit hypercube.
+    ///
+    /// # Panics
+    /// If `y` or `out` do not have one coordinate for each dimension, panics.
+    pub fn inverse_transform(&self, y: &[Real], out: &mut [Real]) {
+        assert!(y.len() == self.ndim() && out.len() == self.ndim());
+        for ((range, &y), out) in self.ranges.iter().zip(y.iter()).zip(out.iter_mut()) {
+            *out = range.inverse_transform(y);
+        }
     }
 }
 
 /// The random number generator source for Cuba's Monte Carlo algorithms. Refer
 /// to Cuba's docs for details and pros/cons of each.
+///
+/// Cuba uses Sobol whenever the seed is zero, and otherwise picks between the
+/// pseudo-random generators by the Ranlux level of its flags (see
+/// `CubaFlags::with_ranlux_level`). So, selecting Mersenne Twister or Ranlux
+/// sets a seed of 1 if the seed was zero, and selecting Sobol sets the seed
+/// to zero.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum RandomNumberSource {
     Sobol,
     MersenneTwister,
+    /// Lüscher's Ranlux, with the given (non-zero) luxury level.
+    Ranlux(u32),
+}
+
+impl RandomNumberSource {
+    /// The source Cuba uses for the given `seed` and `flags`.
+    fn from_seed_and_flags(seed: usize, flags: CubaFlags) -> Self {
+        match (seed, flags.ranlux_level()) {
+            (0, _) => RandomNumberSource::Sobol,
+            (_, 0) => RandomNumberSource::MersenneTwister,
+            (_, level) => RandomNumberSource::Ranlux(level),
+        }
+    }
+
+    /// The seed and flags which select this source, starting from `seed`
+    /// and `flags`.
+    ///
+    /// # Panics
+    /// If the Ranlux level is zero, or doesn't fit in 24 bits, panics.
+    fn apply(self, seed: usize, flags: CubaFlags) -> (usize, CubaFlags) {
+        let nonzero_seed = if seed == 0 { 1 } else { seed };
+        match self {
+            RandomNumberSource::Sobol => (0, flags.with_ranlux_level(0)),
+            RandomNumberSource::MersenneTwister => (nonzero_seed, flags.with_ranlux_level(0)),
+            RandomNumberSource::Ranlux(level) => {
+                assert!(level != 0, "Ranlux level must be non-zero");
+                (nonzero_seed, flags.with_ranlux_level(level))
+            }
+        }
+    }
+}
+
+/// The `flags` word passed to every Cuba integrator, which controls its
+/// output and several details of its algorithm. Each integrator has a
+/// `with_flags` method to set them. The default clears every flag, which is
+/// also Cuba's default.
+///
+/// ```
+/// use integrators::cuba::{CubaFlags, Vegas};
+///
+/// let flags = CubaFlags::new()
+///                       .with_verbosity(1)
+///                       .with_final_iteration_only(true)
+///                       .with_smoothing(false);
+/// assert_eq!(flags.bits(), 0b1101);
+///
+/// let vegas = Vegas::new().with_flags(flags);
+/// assert_eq!(vegas.flags(), flags);
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct CubaFlags {
+    bits: u32,
+}
+
+impl CubaFlags {
+    const VERBOSITY: u32 = 0x3;
+    const FINAL_ITERATION_ONLY: u32 = 0x4;
+    const NO_SMOOTHING: u32 = 0x8;
+    const RETAIN_STATEFILE: u32 = 0x10;
+    const RANLUX_SHIFT: u32 = 8;
+
+    /// The largest Ranlux level which fits in the flags, without setting
+    /// the sign bit of the `int` they are passed to Cuba as.
+    pub const MAX_RANLUX_LEVEL: u32 = (1 << 23) - 1;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn with_bit(self, bit: u32, set: bool) -> Self {
+        CubaFlags {
+            bits: if set { self.bits | bit } else { self.bits & !bit }
+        }
+    }
+
+    /// How much Cuba prints about the integration as it runs, from 0 (print
+    /// nothing) to 3 (print every subregion, or every iteration, and its
+    /// results). (Default = 0)
+    ///
+    /// # Panics
+    /// If `level` is larger than 3, panics.
+    pub fn with_verbosity(self, level: u8) -> Self {
+        assert!(level <= 3, "Cuba verbosity level must be from 0 to 3");
+        CubaFlags {
+            bits: (self.bits & !Self::VERBOSITY) | level as u32
+        }
+    }
+
+    pub fn verbosity(&self) -> u8 {
+        (self.bits & Self::VERBOSITY) as u8
+    }
+
+    /// Compute the results from only the final (largest) iteration, or set
+    /// of samples, rather than from every iteration. Used by Vegas, Suave
+    /// and Divonne. (Default = false)
+    pub fn with_final_iteration_only(self, final_only: bool) -> Self {
+        self.with_bit(Self::FINAL_ITERATION_ONLY, final_only)
+    }
+
+    pub fn final_iteration_only(&self) -> bool {
+        self.bits & Self::FINAL_ITERATION_ONLY != 0
+    }
+
+    /// Smooth the importance function, or the sampling density, which Vegas
+    /// and Suave adapt to the integrand. Other integrators ignore this.
+    /// (Default = true)
+    pub fn with_smoothing(self, smoothing: bool) -> Self {
+        self.with_bit(Self::NO_SMOOTHING, !smoothing)
+    }
+
+    pub fn smoothing(&self) -> bool {
+        self.bits & Self::NO_SMOOTHING == 0
+    }
+
+    /// Keep the state file after the integration succeeds. See each
+    /// integrator's `with_statefile`. (Default = false)
+    pub fn with_retain_statefile(self, retain: bool) -> Self {
+        self.with_bit(Self::RETAIN_STATEFILE, retain)
+    }
+
+    pub fn retain_statefile(&self) -> bool {
+        self.bits & Self::RETAIN_STATEFILE != 0
+    }
+
+    /// The luxury level of the Ranlux random number generator. With a
+    /// non-zero seed, Cuba uses Mersenne Twister if the level is zero, and
+    /// Ranlux otherwise. It's usually simpler to select a generator with
+    /// each integrator's `with_rng`. (Default = 0)
+    ///
+    /// # Panics
+    /// If `level` is larger than `MAX_RANLUX_LEVEL`, panics.
+    pub fn with_ranlux_level(self, level: u32) -> Self {
+        assert!(level <= Self::MAX_RANLUX_LEVEL,
+                "Ranlux level must be at most {}", Self::MAX_RANLUX_LEVEL);
+        CubaFlags {
+            bits: (self.bits & ((1 << Self::RANLUX_SHIFT) - 1)) | (level << Self::RANLUX_SHIFT)
+        }
+    }
+
+    pub fn ranlux_level(&self) -> u32 {
+        self.bits >> Self::RANLUX_SHIFT
+    }
+
+    /// The flags word, as Cuba expects it.
+    pub fn bits(&self) -> c_int {
+        self.bits as c_int
+    }
 }
 
 #[derive(Copy, Clone, Debug, PartialEq)]
@@ -228,6 +929,13 @@ pub enum CubaError {
     /// the desired uncertainty, they still might be useful, and so are
     /// provided.
     DidNotConverge(CubaIntegrationResults),
+    /// The path given for the state file can't be passed to Cuba, because
+    /// it isn't valid UTF-8, or contains a null byte.
+    BadStatefile(PathBuf),
+    /// A point given to Divonne with `with_xgiven` doesn't have as many
+    /// coordinates as the integrand has inputs. The number of coordinates
+    /// of the point, and the number of inputs, are given.
+    BadXGiven(usize, usize),
 }
 
 impl fmt::Display for CubaError {
@@ -242,7 +950,14 @@ impl fmt::Display for CubaError {
                 write!(fmt, "invalid number of outputs for algorithm {}: {}",
                        name, ncomp)
             },
-            &DidNotConverge(_) => write!(fmt, "integral did not converge")
+            &DidNotConverge(_) => write!(fmt, "integral did not converge"),
+            &BadStatefile(ref path) => {
+                write!(fmt, "invalid state file path: {}", path.display())
+            },
+            &BadXGiven(coords, ndim) => {
+                write!(fmt, "divonne given a point with {} coordinates for an integrand \
+                             with {} inputs", coords, ndim)
+            },
         }
     }
 }
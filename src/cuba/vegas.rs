@@ -1,15 +1,21 @@
 use std::{mem, ptr};
-use std::os::raw::{c_int, c_longlong};
+use std::os::raw::{c_int, c_longlong, c_void};
+use std::path::{Path, PathBuf};
 
 use ::bindings;
-use ::ffi::LandingPad;
-use ::traits::{IntegrandInput, IntegrandOutput};
+use ::traits::{BatchIntegrand, BatchIntegrator, IntegrandInput, IntegrandOutput};
 use ::{Integrator, Real};
 
-use super::{cuba_integrand, CubaError, CubaIntegrationResult, CubaIntegrationResults,
-            RandomNumberSource};
+use super::{batch_dims, batch_integrand, cuba_batch_integrand, cuba_integrand,
+            cuba_parallel_integrand, integrand_dims, statefile_cstring, thread_count,
+            CubaBatchIntegrand, CubaError, CubaFlags, CubaIntegrand, CubaIntegrationResult,
+            CubaIntegrationResults, Hyperrectangle, RandomNumberSource, DEFAULT_NVEC};
 
-#[derive(Copy, Clone, Debug)]
+/// The number of slots in Cuba's table of Vegas grids. See
+/// `Vegas::with_grid_slot`.
+pub const MAX_GRID_SLOTS: u8 = 10;
+
+#[derive(Clone, Debug)]
 pub struct Vegas {
     mineval: usize,
     maxeval: usize,
@@ -18,7 +24,12 @@ pub struct Vegas {
     nincrease: usize,
     nbatch: usize,
     gridno: u8,
-    flags: c_int,
+    clear_grid: bool,
+    flags: CubaFlags,
+    domain: Option<Hyperrectangle>,
+    nvec: usize,
+    threads: Option<usize>,
+    statefile: Option<PathBuf>,
 }
 
 impl Default for Vegas {
@@ -31,7 +42,12 @@ impl Default for Vegas {
             nincrease: 500,
             nbatch: 1000,
             gridno: 0,
-            flags: 0
+            clear_grid: false,
+            flags: CubaFlags::default(),
+            domain: None,
+            nvec: DEFAULT_NVEC,
+            threads: None,
+            statefile: None,
         }
     }
 }
@@ -53,7 +69,8 @@ impl Vegas {
         }
     }
 
-    /// Set the random number generator seed.
+    /// Set the random number generator seed. A seed of zero selects Sobol
+    /// numbers. (Default = 0)
     pub fn with_seed(self, seed: usize) -> Self {
         Vegas {
             seed, ..self
@@ -78,65 +95,221 @@ impl Vegas {
         }
     }
 
-    /// Set the random number generator source.
+    /// Keep the grid Vegas adapts to the integrand in slot `slot` of Cuba's
+    /// table of grids, and start each integration from the grid in that
+    /// slot, if it has the same number of dimensions as the integrand. So, a
+    /// family of similar integrands can share one grid, rather than adapting
+    /// a new grid for each. Slots are numbered from 1 to `MAX_GRID_SLOTS`,
+    /// and are shared by every `Vegas` in the process. Cuba doesn't expose
+    /// the contents of the grids, so they can't be read or set from Rust.
+    ///
+    /// # Panics
+    /// If `slot` is zero, or larger than `MAX_GRID_SLOTS`, panics.
+    pub fn with_grid_slot(self, slot: u8) -> Self {
+        assert!(slot >= 1 && slot <= MAX_GRID_SLOTS,
+                "grid slot must be from 1 to {}", MAX_GRID_SLOTS);
+        Vegas {
+            gridno: slot, ..self
+        }
+    }
+
+    /// Don't keep the adapted grid, and start from a uniform grid each
+    /// time. (Default)
+    pub fn without_grid_slot(self) -> Self {
+        Vegas {
+            gridno: 0, clear_grid: false, ..self
+        }
+    }
+
+    pub fn grid_slot(&self) -> Option<u8> {
+        if self.gridno == 0 {
+            None
+        } else {
+            Some(self.gridno)
+        }
+    }
+
+    /// Discard the grid in this integrator's slot at the start of the next
+    /// integration, which then starts from a uniform grid. Its adapted grid
+    /// is stored in the slot as usual, for the integrations after that; this
+    /// only applies to one integration. Has no effect without a grid slot.
+    pub fn with_cleared_grid_slot(self) -> Self {
+        Vegas {
+            clear_grid: self.gridno != 0, ..self
+        }
+    }
+
+    /// Whether the next integration will discard the grid in this
+    /// integrator's slot (see `with_cleared_grid_slot`).
+    pub fn clears_grid_slot(&self) -> bool {
+        self.clear_grid
+    }
+
+    /// Set the random number generator source. See `RandomNumberSource`
+    /// for how this interacts with the seed.
+    ///
+    /// # Panics
+    /// If given `RandomNumberSource::Ranlux` with a level of zero, or larger
+    /// than `CubaFlags::MAX_RANLUX_LEVEL`, panics.
     pub fn with_rng(self, rng: RandomNumberSource) -> Self {
+        let (seed, flags) = rng.apply(self.seed, self.flags);
         Vegas {
-            flags: (self.flags & !0x8) & match rng {
-                RandomNumberSource::Sobol => 0,
-                RandomNumberSource::MersenneTwister => 8,
-            }, ..self
+            seed, flags, ..self
         }
     }
-}
 
-impl Integrator for Vegas {
-    type Success = CubaIntegrationResults;
-    type Failure = super::CubaError;
-    fn integrate<A, B, F: FnMut(A) -> B>(&mut self, mut fun: F, epsrel: Real, epsabs: Real) -> Result<Self::Success, Self::Failure>
+    pub fn rng(&self) -> RandomNumberSource {
+        RandomNumberSource::from_seed_and_flags(self.seed, self.flags)
+    }
+
+    /// Integrate over `domain`, rather than Cuba's unit hypercube. The
+    /// integrand is called with arguments in `domain`, and the Jacobian of
+    /// the transformation is applied automatically.
+    pub fn with_domain(self, domain: Hyperrectangle) -> Self {
+        Vegas {
+            domain: Some(domain), ..self
+        }
+    }
+
+    /// Integrate over Cuba's unit hypercube. (Default)
+    pub fn without_domain(self) -> Self {
+        Vegas {
+            domain: None, ..self
+        }
+    }
+
+    pub fn domain(&self) -> Option<&Hyperrectangle> {
+        self.domain.as_ref()
+    }
+
+    /// Save the state of the integration to the file at `path` as it goes,
+    /// and resume from that state, if the file exists, when integration
+    /// starts. So, an integration which is interrupted can be resumed from
+    /// where it left off, by integrating the same integrand again with the
+    /// same parameters. Unless `with_retain_statefile(true)` is set, the
+    /// file is deleted once the integration succeeds.
+    pub fn with_statefile<P: AsRef<Path>>(self, path: P) -> Self {
+        Vegas {
+            statefile: Some(path.as_ref().to_path_buf()), ..self
+        }
+    }
+
+    /// Don't save the state of the integration. (Default)
+    pub fn without_statefile(self) -> Self {
+        Vegas {
+            statefile: None, ..self
+        }
+    }
+
+    pub fn statefile(&self) -> Option<&Path> {
+        self.statefile.as_ref().map(|path| path.as_path())
+    }
+
+    /// Keep the state file after the integration succeeds, e.g. to
+    /// continue the integration to a tighter tolerance later.
+    /// (Default = false)
+    pub fn with_retain_statefile(self, retain: bool) -> Self {
+        Vegas {
+            flags: self.flags.with_retain_statefile(retain), ..self
+        }
+    }
+
+    /// Set all of Cuba's flags at once, including those set by
+    /// `with_retain_statefile` and `with_rng`. See `CubaFlags`.
+    /// (Default = no flags)
+    pub fn with_flags(self, flags: CubaFlags) -> Self {
+        Vegas {
+            flags, ..self
+        }
+    }
+
+    pub fn flags(&self) -> CubaFlags {
+        self.flags
+    }
+
+    /// The largest number of points passed to the integrand at once by
+    /// `integrate_parallel`, to be split between threads, and by
+    /// `integrate_batch`. (Default = 1024)
+    pub fn with_nvec(self, nvec: usize) -> Self {
+        Vegas {
+            nvec: nvec.max(1), ..self
+        }
+    }
+
+    /// The number of threads `integrate_parallel` evaluates the integrand
+    /// on. (Default = the available parallelism of the machine)
+    pub fn with_threads(self, threads: usize) -> Self {
+        Vegas {
+            threads: Some(threads.max(1)), ..self
+        }
+    }
+
+    /// Integrates like `integrate`, but has Cuba pass the integrand batches
+    /// of up to `nvec` points (see `with_nvec`), which are evaluated in
+    /// parallel on Rust threads (see `with_threads`). A panic in the
+    /// integrand, on any thread, aborts the integration and is resumed here.
+    pub fn integrate_parallel<A, B, F>(&mut self, mut fun: F, epsrel: Real, epsabs: Real)
+            -> Result<CubaIntegrationResults, CubaError>
         where A: IntegrandInput,
-              B: IntegrandOutput
+              B: IntegrandOutput,
+              F: Fn(A) -> B + Sync
     {
+        let (ndim, ncomp) = integrand_dims("vegas", &mut fun, self.domain.as_ref())?;
+
+        let mut integrand = CubaIntegrand::new(fun, self.domain.as_ref())
+                                          .with_threads(thread_count(self.threads));
+        let res = unsafe {
+            self.run(ndim, ncomp,
+                     batch_integrand(cuba_parallel_integrand::<A, B, F>), mem::transmute(&mut integrand),
+                     self.nvec, epsrel, epsabs)
+        };
+        self.clear_grid = false;
+        integrand.maybe_resume_unwind();
+        res
+    }
+
+    /// Runs Vegas on `integrand`, which is called with `userdata` and
+    /// batches of up to `nvec` points.
+    unsafe fn run(&self, ndim: usize, ncomp: usize,
+                  integrand: bindings::integrand_t, userdata: *mut c_void, nvec: usize,
+                  epsrel: Real, epsabs: Real) -> Result<CubaIntegrationResults, CubaError> {
         // Using cuba's parallelization via fork() would deeply break Rust's
         // concurrency model and safety guarantees. So, we'll turn it off.
-        unsafe { bindings::cubacores(0, 0) };
+        bindings::cubacores(0, 0);
 
-        let (ndim, ncomp) = {
-            let inputs = A::input_size();
-            let outputs = fun(A::from_args(&vec![0.5; inputs][..])).output_size();
-            (inputs, outputs)
+        let statefile = statefile_cstring(self.statefile.as_ref())?;
+        // A negative slot number has Cuba clear the slot before integrating
+        let gridno = if self.clear_grid {
+            -(self.gridno as c_int)
+        } else {
+            self.gridno as c_int
         };
-
         let mut neval = 0;
         let mut fail = 0;
         let (mut value, mut error, mut prob) =
                 (vec![0.0; ncomp], vec![0.0; ncomp], vec![0.0; ncomp]);
 
-        let mut lp = LandingPad::new(fun);
-        unsafe {
-            bindings::llVegas(ndim as c_int, ncomp as c_int,
-                              Some(cuba_integrand::<A, B, F>), mem::transmute(&mut lp),
-                              1 /* nvec */,
-                              epsrel,
-                              epsabs,
-                              self.flags,
-                              self.seed as c_int,
-                              self.mineval as c_longlong,
-                              self.maxeval as c_longlong,
-                              self.nstart as c_longlong,
-                              self.nincrease as c_longlong,
-                              self.nbatch as c_longlong,
-                              self.gridno as c_int,
-                              // statefile
-                              ptr::null(),
-                              // spin
-                              ptr::null_mut(),
-                              &mut neval,
-                              &mut fail,
-                              value.as_mut_ptr(),
-                              error.as_mut_ptr(),
-                              prob.as_mut_ptr());
-        }
-        lp.maybe_resume_unwind();
+        bindings::llVegas(ndim as c_int, ncomp as c_int,
+                          integrand, userdata,
+                          nvec as c_longlong,
+                          epsrel,
+                          epsabs,
+                          self.flags.bits(),
+                          self.seed as c_int,
+                          self.mineval as c_longlong,
+                          self.maxeval as c_longlong,
+                          self.nstart as c_longlong,
+                          self.nincrease as c_longlong,
+                          self.nbatch as c_longlong,
+                          gridno,
+                          statefile.as_ref().map(|path| path.as_ptr()).unwrap_or(ptr::null()),
+                          // spin
+                          ptr::null_mut(),
+                          &mut neval,
+                          &mut fail,
+                          value.as_mut_ptr(),
+                          error.as_mut_ptr(),
+                          prob.as_mut_ptr());
 
         if fail == 0 {
             Ok(CubaIntegrationResults {
@@ -169,3 +342,44 @@ impl Integrator for Vegas {
         }
     }
 }
+
+impl Integrator for Vegas {
+    type Success = CubaIntegrationResults;
+    type Failure = super::CubaError;
+    fn integrate<A, B, F: FnMut(A) -> B>(&mut self, mut fun: F, epsrel: Real, epsabs: Real) -> Result<Self::Success, Self::Failure>
+        where A: IntegrandInput,
+              B: IntegrandOutput
+    {
+        let (ndim, ncomp) = integrand_dims("vegas", &mut fun, self.domain.as_ref())?;
+
+        let mut integrand = CubaIntegrand::new(fun, self.domain.as_ref());
+        let res = unsafe {
+            self.run(ndim, ncomp,
+                     Some(cuba_integrand::<A, B, F>), mem::transmute(&mut integrand),
+                     1, epsrel, epsabs)
+        };
+        self.clear_grid = false;
+        integrand.maybe_resume_unwind();
+        res
+    }
+}
+
+impl BatchIntegrator for Vegas {
+    type Success = CubaIntegrationResults;
+    type Failure = super::CubaError;
+    fn integrate_batch<I>(&mut self, integrand: I, epsrel: Real, epsabs: Real) -> Result<Self::Success, Self::Failure>
+        where I: BatchIntegrand
+    {
+        let (ndim, ncomp) = batch_dims("vegas", &integrand, self.domain.as_ref())?;
+
+        let mut integrand = CubaBatchIntegrand::new(integrand, self.domain.as_ref());
+        let res = unsafe {
+            self.run(ndim, ncomp,
+                     batch_integrand(cuba_batch_integrand::<I>), mem::transmute(&mut integrand),
+                     self.nvec, epsrel, epsabs)
+        };
+        self.clear_grid = false;
+        integrand.maybe_resume_unwind();
+        res
+    }
+}
@@ -5,10 +5,14 @@
 //! Once foreign code has finished calling Rust code, the `LandingPad` can
 //! be inspected to see if it has caught any panics, and resume the panic
 //! safely through Rust code.
+//!
+//! `Workers` is a set of threads which a `LandingPad` can split batches of
+//! points between, with `try_call_parallel()`.
 
 use std::any::Any;
 use std::marker::PhantomData;
-use std::panic;
+use std::sync::{mpsc, Arc, Mutex};
+use std::{cmp, mem, panic, thread};
 use ::traits::{IntegrandInput, IntegrandOutput};
 use ::Real;
 
@@ -60,6 +64,27 @@ impl<A: IntegrandInput, B: IntegrandOutput, F: FnMut(A) -> B> LandingPad<A, B, F
         }
     }
 
+    /// Runs an arbitrary closure `g` under the `LandingPad`, catching any
+    /// panic it raises. This is meant for auxiliary callbacks from foreign
+    /// code (such as Cuba's peakfinder), which should share the panic
+    /// handling of the integrand itself.
+    ///
+    /// As with `try_call()`, if a panic has already been caught, `g` is not
+    /// run, and a reference to the earlier panic is returned.
+    pub fn try_run<T, G: FnOnce() -> T>(&mut self, g: G) -> Result<T, &(Any + Send + 'static)> {
+        if self.err.is_some() {
+            Err(self.err.as_ref().expect("just said it is some"))
+        } else {
+            match panic::catch_unwind(panic::AssertUnwindSafe(g)) {
+                Ok(res) => Ok(res),
+                Err(err) => {
+                    self.err = Some(err);
+                    Err(self.err.as_ref().expect("just set to Some(..)"))
+                }
+            }
+        }
+    }
+
     pub fn raw_call(&mut self, args: &[Real]) -> B {
         (self.fun)(A::from_args(args))
     }
@@ -78,3 +103,137 @@ impl<A: IntegrandInput, B: IntegrandOutput, F: FnMut(A) -> B> LandingPad<A, B, F
         panic::resume_unwind(self.err.expect("trying to resume unwind"))
     }
 }
+
+impl<A, B, F> LandingPad<A, B, F>
+    where A: IntegrandInput,
+          B: IntegrandOutput,
+          F: Fn(A) -> B + Sync
+{
+    /// Applies the integrand to a batch of points, splitting them between the
+    /// calling thread and `workers`. `args` holds the points one after
+    /// another, each with `A::input_size()` coordinates, and `output`
+    /// receives their outputs in the same order, which must all have the
+    /// same size.
+    ///
+    /// Panics in any of the threads are caught, as with `try_call()`; if
+    /// several threads panic, the first of their panics is kept.
+    pub fn try_call_parallel(&mut self, args: &[Real], output: &mut [Real], workers: &Workers)
+            -> Result<(), &(Any + Send + 'static)> {
+        if self.err.is_some() {
+            return Err(self.err.as_ref().expect("just said it is some"))
+        }
+
+        let ndim = A::input_size();
+        assert!(ndim > 0 && args.len() % ndim == 0,
+                "arguments must hold a whole number of points");
+        let npoints = args.len() / ndim;
+        if npoints == 0 {
+            return Ok(())
+        }
+        let ncomp = output.len() / npoints;
+        assert!(ncomp > 0 && output.len() == npoints * ncomp,
+                "output must hold the same, non-zero number of values for each point");
+        let nthreads = workers.threads();
+        let chunk = (npoints + nthreads - 1) / nthreads;
+
+        let fun = &self.fun;
+        let call_all = move |args: &[Real], output: &mut [Real]| {
+            panic::catch_unwind(panic::AssertUnwindSafe(|| {
+                for (x, out) in args.chunks(ndim).zip(output.chunks_mut(ncomp)) {
+                    fun(A::from_args(x)).into_args(out)
+                }
+            })).err()
+        };
+
+        let mut chunks = args.chunks(chunk * ndim).zip(output.chunks_mut(chunk * ncomp));
+        let (own_args, own_output) = chunks.next().expect("there is at least one point");
+
+        // Nothing below may panic until every job has reported back, since
+        // the jobs borrow `args` and `output`
+        let (done, results) = mpsc::channel();
+        let mut njobs = 0;
+        for (args, output) in chunks {
+            let done = done.clone();
+            workers.run(Box::new(move || {
+                let _ = done.send(call_all(args, output));
+            }));
+            njobs += 1;
+        }
+        let err = call_all(own_args, own_output);
+        let err = results.iter().take(njobs).fold(err, |first, err| first.or(err));
+
+        match err {
+            None => Ok(()),
+            Some(err) => {
+                self.err = Some(err);
+                Err(self.err.as_ref().expect("just set to Some(..)"))
+            }
+        }
+    }
+}
+
+type Job = Box<FnOnce() + Send + 'static>;
+
+/// A fixed set of threads, which `LandingPad::try_call_parallel()` hands
+/// batches of points to. The threads are started once, when the `Workers`
+/// are created, and reused for every batch; they finish when it is dropped.
+pub struct Workers {
+    jobs: Option<mpsc::Sender<Job>>,
+    threads: Vec<thread::JoinHandle<()>>,
+}
+
+impl Workers {
+    /// Prepares to split batches between `nthreads` threads, which includes
+    /// the thread calling `try_call_parallel()`; so `nthreads - 1` threads
+    /// are started.
+    pub fn new(nthreads: usize) -> Self {
+        let (jobs, queue) = mpsc::channel::<Job>();
+        let queue = Arc::new(Mutex::new(queue));
+        let threads = (1..cmp::max(nthreads, 1)).map(|_| {
+            let queue = queue.clone();
+            thread::spawn(move || loop {
+                let job = match queue.lock() {
+                    Ok(queue) => queue.recv(),
+                    Err(_) => return,
+                };
+                match job {
+                    Ok(job) => job(),
+                    Err(_) => return,
+                }
+            })
+        }).collect();
+        Workers { jobs: Some(jobs), threads }
+    }
+
+    /// The number of threads batches are split between, including the
+    /// calling thread.
+    pub fn threads(&self) -> usize {
+        self.threads.len() + 1
+    }
+
+    /// Runs `job` on one of the threads. The caller must not return until
+    /// `job` has finished, since the borrows in it are extended to
+    /// `'static` here.
+    fn run<'a>(&self, job: Box<FnOnce() + Send + 'a>) {
+        let job: Job = unsafe { mem::transmute(job) };
+        let job = match self.jobs {
+            Some(ref jobs) => match jobs.send(job) {
+                Ok(()) => return,
+                Err(mpsc::SendError(job)) => job,
+            },
+            None => job,
+        };
+        // The threads are gone, so run it here instead
+        job()
+    }
+}
+
+impl Drop for Workers {
+    fn drop(&mut self) {
+        // Closing the queue stops the threads
+        self.jobs = None;
+        for thread in self.threads.drain(..) {
+            let _ = thread.join();
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use std::collections::HashMap;
+
+use ::{Integrator, Real};
+use ::traits::{IntegrandInput, IntegrandOutput};
+
+use super::GSLIntegrationError;
+
+/// Adapts any GSL integrator to integrands with several outputs, by
+/// integrating each component of the output separately, with the same
+/// tolerances. The results are returned in a `Vec`, one for each component.
+///
+/// Evaluations of the integrand are cached while integrating, and shared
+/// between the components; so, wherever the integrator samples the same
+/// points for different components (e.g. the initial quadrature rules of
+/// adaptive integrators, or every point of a fixed rule), the integrand is
+/// only evaluated once. This makes it worthwhile for integrands which share
+/// an expensive computation between components. The cache holds at most
+/// `cache_size` points (see `with_cache_size`), and is emptied as the last
+/// component is integrated.
+///
+/// ```
+/// use integrators::{gsl, Integrator, Real};
+/// let mut moments = gsl::Componentwise::new(gsl::QAG::new(100));
+///
+/// let res = moments.integrate(|x: Real| vec![1.0, x, x * x], 1e-10, 1e-12)
+///                  .unwrap();
+/// assert_eq!(res.len(), 3);
+/// for (i, r) in res.iter().enumerate() {
+///     assert!((r.value - 1.0 / (i as Real + 1.0)).abs() < 1e-10);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Componentwise<I> {
+    integrator: I,
+    cache_size: usize,
+}
+
+impl<I> Componentwise<I> {
+    /// Wraps `integrator`, which will integrate each component.
+    pub fn new(integrator: I) -> Self {
+        Componentwise { integrator, cache_size: 10000 }
+    }
+
+    /// Cache the outputs of the integrand at no more than `cache_size`
+    /// points. Once the cache is full, points outside it are evaluated again
+    /// for each component. (Default = 10000)
+    pub fn with_cache_size(self, cache_size: usize) -> Self {
+        Componentwise { cache_size, ..self }
+    }
+
+    pub fn integrator(&self) -> &I {
+        &self.integrator
+    }
+
+    pub fn integrator_mut(&mut self) -> &mut I {
+        &mut self.integrator
+    }
+
+    pub fn into_inner(self) -> I {
+        self.integrator
+    }
+}
+
+impl<I> Integrator for Componentwise<I>
+    where I: Integrator<Failure = GSLIntegrationError>
+{
+    type Success = Vec<I::Success>;
+    type Failure = GSLIntegrationError;
+    fn integrate<A, B, F: FnMut(A) -> B>(&mut self, mut fun: F, epsrel: Real, epsabs: Real) -> Result<Self::Success, Self::Failure>
+        where A: IntegrandInput,
+              B: IntegrandOutput
+    {
+        if A::input_size() != 1 {
+            return Err(GSLIntegrationError::InvalidInputDim(A::input_size()))
+        }
+
+        // Keyed by the bits of the argument, as floats aren't hashable
+        let mut cache: HashMap<u64, Vec<Real>> = HashMap::new();
+        let mut ncomp: Option<usize> = None;
+        let mut results = Vec::new();
+        let cache_size = self.cache_size;
+
+        let mut component = 0;
+        while ncomp.map(|n| component < n).unwrap_or(true) {
+            // No later component needs the cached points, so each is taken
+            // out of the cache as it's used
+            let last_component = ncomp == Some(component + 1);
+            let res = {
+                let cache = &mut cache;
+                let ncomp = &mut ncomp;
+                let fun = &mut fun;
+                self.integrator.integrate(|x: Real| {
+                    let key = x.to_bits();
+                    let cached = if last_component {
+                        cache.remove(&key)
+                    } else {
+                        cache.get(&key).cloned()
+                    };
+                    let output = cached.unwrap_or_else(|| {
+                        let out = fun(A::from_args(&[x]));
+                        let mut output = vec![0.0; out.output_size()];
+                        out.into_args(&mut output[..]);
+                        if !last_component && cache.len() < cache_size {
+                            cache.insert(key, output.clone());
+                        }
+                        output
+                    });
+                    let n = *ncomp.get_or_insert(output.len());
+                    if output.len() != n {
+                        panic!("Integrand returned output of inconsistent size: expected {}, got {}",
+                               n, output.len());
+                    }
+                    output.get(component).cloned().unwrap_or(0.0)
+                }, epsrel, epsabs)?
+            };
+            if ncomp == Some(0) {
+                return Err(GSLIntegrationError::InvalidOutputDim(0))
+            }
+            results.push(res);
+            component += 1;
+        }
+
+        Ok(results)
+    }
+}
@@ -0,0 +1,164 @@
+use std::fmt;
+
+use ::bindings;
+use ::{IntegrationResult, IntegrationResultIter, Integrator, Real};
+use ::ffi::LandingPad;
+use ::traits::{IntegrandInput, IntegrandOutput, IntegrationResults};
+
+use super::{make_gsl_function, GSLIntegrationError};
+
+struct CQUADWorkspace {
+    nintervals: usize,
+    wkspc: *mut bindings::gsl_integration_cquad_workspace
+}
+
+impl fmt::Debug for CQUADWorkspace {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("CQUADWorkspace")
+           .field("nintervals", &self.nintervals)
+           .finish()
+    }
+}
+
+impl Clone for CQUADWorkspace {
+    fn clone(&self) -> Self {
+        CQUADWorkspace::new(self.nintervals)
+    }
+}
+
+impl CQUADWorkspace {
+    fn new(n: usize) -> Self {
+        assert!(n >= 3, "CQUAD workspace needs at least 3 intervals");
+        CQUADWorkspace {
+            // TODO: Check for null-pointer
+            wkspc: unsafe {
+                bindings::gsl_integration_cquad_workspace_alloc(n)
+            },
+            nintervals: n
+        }
+    }
+}
+
+impl Drop for CQUADWorkspace {
+    fn drop(&mut self) {
+        unsafe {
+            bindings::gsl_integration_cquad_workspace_free(self.wkspc)
+        }
+    }
+}
+
+/// The result of a `CQUAD` integration, along with the number of integrand
+/// evaluations it used.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct CQUADResult {
+    pub value: Real,
+    pub error: Real,
+    /// The number of integrand evaluations used.
+    pub neval: usize,
+}
+
+impl IntegrationResults for CQUADResult {
+    type Iterator = IntegrationResultIter;
+    fn results(self) -> Self::Iterator {
+        IntegrationResult {
+            value: self.value,
+            error: self.error,
+        }.results()
+    }
+}
+
+/// Doubly-adaptive integration, based on Clenshaw-Curtis quadrature rules
+/// of increasing degree on adaptively bisected subintervals. CQUAD is robust
+/// against integrands containing `NaN`s or infinities (for example at an
+/// endpoint, where an expression has a 0/0 limit), and against some
+/// non-integrable singularities.
+///
+/// Along with the value and error estimate, CQUAD reports the number of
+/// integrand evaluations used.
+///
+/// See GSL docs
+/// [here](https://www.gnu.org/software/gsl/doc/html/integration.html#cquad-doubly-adaptive-integration).
+///
+/// ```
+/// use integrators::{gsl, Integrator, Real};
+/// let mut cquad = gsl::CQUAD::new(100);
+///
+/// // Evaluates to NaN at x = 0
+/// let res = cquad.integrate(|x: Real| x.sin() / x, 1e-10, 1e-12)
+///                .unwrap();
+/// assert!((res.value - 0.946083070367183).abs() < 1e-10);
+/// assert!(res.neval > 0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct CQUAD {
+    range_low: Real,
+    range_high: Real,
+    wkspc: CQUADWorkspace,
+}
+
+impl CQUAD {
+    /// Creates a new CQUAD with enough memory for `nintervals` subintervals.
+    /// This will integrate over the range [0, 1]; to change the integration
+    /// bounds, see `with_range`.
+    ///
+    /// # Panics
+    /// If `nintervals` is less than 3, panics.
+    pub fn new(nintervals: usize) -> Self {
+        CQUAD {
+            range_low: 0.0,
+            range_high: 1.0,
+            wkspc: CQUADWorkspace::new(nintervals)
+        }
+    }
+
+    /// Discards the old workspace and allocates a new one with enough memory
+    /// for `nintervals` subintervals.
+    ///
+    /// # Panics
+    /// If `nintervals` is less than 3, panics.
+    pub fn with_nintervals(self, nintervals: usize) -> Self {
+        CQUAD {
+            wkspc: CQUADWorkspace::new(nintervals),
+            ..self
+        }
+    }
+
+    /// Use a different integration range. (Default = [0, 1])
+    pub fn with_range(self, range_low: Real, range_high: Real) -> Self {
+        CQUAD { range_low, range_high, ..self }
+    }
+}
+
+impl Integrator for CQUAD {
+    type Success = CQUADResult;
+    type Failure = GSLIntegrationError;
+    fn integrate<A, B, F: FnMut(A) -> B>(&mut self, fun: F, epsrel: Real, epsabs: Real) -> Result<Self::Success, Self::Failure>
+        where A: IntegrandInput,
+              B: IntegrandOutput
+    {
+        let mut value: Real = 0.0;
+        let mut error: Real = 0.0;
+        let mut neval: usize = 0;
+
+        let mut lp = LandingPad::new(fun);
+        let retcode = unsafe {
+            let mut gslfn = make_gsl_function(&mut lp, self.range_low, self.range_high)?;
+            bindings::gsl_integration_cquad(&mut gslfn.function,
+                                            self.range_low, self.range_high,
+                                            epsabs, epsrel,
+                                            self.wkspc.wkspc,
+                                            &mut value,
+                                            &mut error,
+                                            &mut neval)
+        };
+        lp.maybe_resume_unwind();
+
+        if retcode != bindings::GSL_SUCCESS {
+            Err(GSLIntegrationError::from_retcode(retcode))
+        } else {
+            Ok(CQUADResult {
+                value, error, neval
+            })
+        }
+    }
+}
@@ -0,0 +1,229 @@
+use std::{fmt, slice};
+
+use ::bindings;
+use ::{IntegrationResult, Integrator, Real};
+use ::ffi::LandingPad;
+use ::traits::{IntegrandInput, IntegrandOutput};
+
+use super::{handler, make_gsl_function, GSLIntegrationError};
+
+/// The weight function `W(x)`, and the corresponding family of orthogonal
+/// polynomials, of a fixed-order quadrature rule. Every family also takes
+/// the two parameters `a` and `b` given to `FixedQuadrature::new`, whose
+/// meaning is described for each variant.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum FixedWeight {
+    /// Gauss-Legendre: `W(x) = 1` over `(a, b)`.
+    Legendre,
+    /// Gauss-Chebyshev (first kind): `W(x) = 1 / sqrt((b - x) (x - a))`
+    /// over `(a, b)`.
+    Chebyshev,
+    /// Gauss-Chebyshev (second kind): `W(x) = sqrt((b - x) (x - a))`
+    /// over `(a, b)`.
+    Chebyshev2,
+    /// Gauss-Gegenbauer: `W(x) = ((b - x) (x - a))^alpha` over `(a, b)`.
+    /// Requires `alpha > -1`.
+    Gegenbauer { alpha: Real },
+    /// Gauss-Jacobi: `W(x) = (b - x)^alpha (x - a)^beta` over `(a, b)`.
+    /// Requires `alpha > -1` and `beta > -1`.
+    Jacobi { alpha: Real, beta: Real },
+    /// Generalized Gauss-Laguerre: `W(x) = (x - a)^alpha exp(-b (x - a))`
+    /// over `(a, +inf)`. Requires `alpha > -1` and `b > 0`.
+    Laguerre { alpha: Real },
+    /// Generalized Gauss-Hermite: `W(x) = |x - a|^alpha exp(-b (x - a)^2)`
+    /// over `(-inf, +inf)`. Requires `alpha > -1` and `b > 0`.
+    Hermite { alpha: Real },
+    /// Exponential: `W(x) = |x - (a + b) / 2|^alpha` over `(a, b)`.
+    /// Requires `alpha > -1`.
+    Exponential { alpha: Real },
+    /// Rational: `W(x) = (x - a)^alpha (x + b)^beta` over `(a, +inf)`.
+    /// Requires `alpha > -1`, `a + b > 0`, and `alpha + beta + 2n < 0`.
+    Rational { alpha: Real, beta: Real },
+}
+
+impl FixedWeight {
+    fn params(&self) -> (Real, Real) {
+        use self::FixedWeight::*;
+        match *self {
+            Legendre | Chebyshev | Chebyshev2 => (0.0, 0.0),
+            Gegenbauer { alpha } | Laguerre { alpha }
+                | Hermite { alpha } | Exponential { alpha } => (alpha, 0.0),
+            Jacobi { alpha, beta } | Rational { alpha, beta } => (alpha, beta),
+        }
+    }
+
+    fn gsl_type(&self) -> *const bindings::gsl_integration_fixed_type {
+        use self::FixedWeight::*;
+        unsafe {
+            match *self {
+                Legendre => bindings::gsl_integration_fixed_legendre,
+                Chebyshev => bindings::gsl_integration_fixed_chebyshev,
+                Chebyshev2 => bindings::gsl_integration_fixed_chebyshev2,
+                Gegenbauer { .. } => bindings::gsl_integration_fixed_gegenbauer,
+                Jacobi { .. } => bindings::gsl_integration_fixed_jacobi,
+                Laguerre { .. } => bindings::gsl_integration_fixed_laguerre,
+                Hermite { .. } => bindings::gsl_integration_fixed_hermite,
+                Exponential { .. } => bindings::gsl_integration_fixed_exponential,
+                Rational { .. } => bindings::gsl_integration_fixed_rational,
+            }
+        }
+    }
+
+    /// Mirrors the simpler parameter checks GSL performs, to avoid calling
+    /// GSL with parameters which are obviously invalid. GSL may still reject
+    /// parameters which pass, e.g. a range narrower than machine epsilon.
+    fn is_valid(&self, n: usize, a: Real, b: Real) -> bool {
+        use self::FixedWeight::*;
+        let (alpha, beta) = self.params();
+        n > 0 && match *self {
+            Legendre | Chebyshev | Chebyshev2 => a < b,
+            Gegenbauer { .. } | Exponential { .. } => alpha > -1.0 && a < b,
+            Jacobi { .. } => alpha > -1.0 && beta > -1.0 && a < b,
+            Laguerre { .. } | Hermite { .. } => alpha > -1.0 && b > 0.0,
+            Rational { .. } => alpha > -1.0 && a + b > 0.0
+                               && alpha + beta + 2.0 * (n as Real) < 0.0,
+        }
+    }
+}
+
+/// A fixed-order, `n`-point Gaussian quadrature rule, for one of the weight
+/// functions described by `FixedWeight`. Integrating `f` computes
+/// `\int W(x) f(x) dx` as the weighted sum of `f` at the nodes of the rule,
+/// which is exact when `f` is a polynomial of degree `2n - 1` or less.
+///
+/// Since the rule is fixed, no error estimate is available; the reported
+/// error is always zero, and the tolerances given to `integrate` are
+/// ignored.
+///
+/// Requires GSL 2.3 or newer. See GSL docs
+/// [here](https://www.gnu.org/software/gsl/doc/html/integration.html#fixed-point-quadratures).
+///
+/// ```
+/// use integrators::{gsl, Integrator, Real};
+/// // E[x^2] for a standard normal distribution, with Gauss-Hermite
+/// // quadrature: W(x) = exp(-x^2 / 2)
+/// let mut hermite = gsl::FixedQuadrature::new(gsl::FixedWeight::Hermite { alpha: 0.0 },
+///                                             10, 0.0, 0.5).unwrap();
+/// let norm = (2.0 * ::std::f64::consts::PI).sqrt();
+/// let res = hermite.integrate(|x: Real| x * x / norm, 0.0, 0.0)
+///                  .unwrap();
+/// assert!((res.value - 1.0).abs() < 1e-12);
+/// ```
+pub struct FixedQuadrature {
+    weight: FixedWeight,
+    a: Real,
+    b: Real,
+    wkspc: *mut bindings::gsl_integration_fixed_workspace,
+}
+
+impl fmt::Debug for FixedQuadrature {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("FixedQuadrature")
+           .field("weight", &self.weight)
+           .field("n", &self.n())
+           .field("a", &self.a)
+           .field("b", &self.b)
+           .finish()
+    }
+}
+
+impl Clone for FixedQuadrature {
+    fn clone(&self) -> Self {
+        FixedQuadrature::new(self.weight, self.n(), self.a, self.b)
+            .expect("parameters were already validated")
+    }
+}
+
+impl FixedQuadrature {
+    /// Creates a new `n`-point quadrature rule for the weight function
+    /// `weight`, with parameters `a` and `b` (see `FixedWeight` for their
+    /// meaning). Returns `None` if the parameters are invalid for the given
+    /// weight function, or if `n` is zero, or if GSL otherwise fails to
+    /// compute the rule.
+    pub fn new(weight: FixedWeight, n: usize, a: Real, b: Real) -> Option<Self> {
+        if !weight.is_valid(n, a, b) {
+            return None
+        }
+        let (alpha, beta) = weight.params();
+        // GSL reports invalid parameters to its error handler, which would
+        // otherwise abort the process
+        let _guard = handler::ErrorHandlerGuard::new();
+        let wkspc = unsafe {
+            bindings::gsl_integration_fixed_alloc(weight.gsl_type(), n,
+                                                  a, b, alpha, beta)
+        };
+        let failed = handler::take_any_error().is_some();
+        if wkspc.is_null() {
+            None
+        } else if failed {
+            unsafe { bindings::gsl_integration_fixed_free(wkspc) };
+            None
+        } else {
+            Some(FixedQuadrature { weight, a, b, wkspc })
+        }
+    }
+
+    pub fn weight(&self) -> FixedWeight {
+        self.weight
+    }
+
+    /// The number of nodes of the quadrature rule.
+    pub fn n(&self) -> usize {
+        unsafe { bindings::gsl_integration_fixed_n(self.wkspc) }
+    }
+
+    /// The nodes at which the integrand is evaluated.
+    pub fn nodes(&self) -> &[Real] {
+        unsafe {
+            slice::from_raw_parts(bindings::gsl_integration_fixed_nodes(self.wkspc),
+                                  self.n())
+        }
+    }
+
+    /// The weights of the nodes. The integral is the sum of the integrand at
+    /// each node, times these weights.
+    pub fn weights(&self) -> &[Real] {
+        unsafe {
+            slice::from_raw_parts(bindings::gsl_integration_fixed_weights(self.wkspc),
+                                  self.n())
+        }
+    }
+}
+
+impl Drop for FixedQuadrature {
+    fn drop(&mut self) {
+        unsafe {
+            bindings::gsl_integration_fixed_free(self.wkspc)
+        }
+    }
+}
+
+impl Integrator for FixedQuadrature {
+    type Success = IntegrationResult;
+    type Failure = GSLIntegrationError;
+    fn integrate<A, B, F: FnMut(A) -> B>(&mut self, fun: F, _epsrel: Real, _epsabs: Real) -> Result<Self::Success, Self::Failure>
+        where A: IntegrandInput,
+              B: IntegrandOutput
+    {
+        let mut value: Real = 0.0;
+        // The range may be infinite, so check the integrand at the first node
+        let node = self.nodes()[0];
+
+        let mut lp = LandingPad::new(fun);
+        let retcode = unsafe {
+            let mut gslfn = make_gsl_function(&mut lp, node, node)?;
+            bindings::gsl_integration_fixed(&mut gslfn.function,
+                                            &mut value,
+                                            self.wkspc)
+        };
+        lp.maybe_resume_unwind();
+
+        if retcode != bindings::GSL_SUCCESS {
+            Err(GSLIntegrationError::from_retcode(retcode))
+        } else {
+            Ok(IntegrationResult {
+                value, error: 0.0
+            })
+        }
+    }
+}
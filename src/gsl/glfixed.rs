@@ -0,0 +1,200 @@
+use std::fmt;
+
+use ::bindings;
+use ::{IntegrationResult, Integrator, Real};
+use ::traits::{BatchIntegrand, BatchIntegrator, IntegrandInput, IntegrandOutput};
+
+use super::GSLIntegrationError;
+
+struct GLFixedTable {
+    n: usize,
+    table: *mut bindings::gsl_integration_glfixed_table
+}
+
+impl fmt::Debug for GLFixedTable {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("GLFixedTable")
+           .field("n", &self.n)
+           .finish()
+    }
+}
+
+impl Clone for GLFixedTable {
+    fn clone(&self) -> Self {
+        GLFixedTable::new(self.n)
+    }
+}
+
+impl GLFixedTable {
+    fn new(n: usize) -> Self {
+        assert!(n >= 1, "Gauss-Legendre table needs at least one point");
+        GLFixedTable {
+            // TODO: Check for null-pointer
+            table: unsafe {
+                bindings::gsl_integration_glfixed_table_alloc(n)
+            },
+            n
+        }
+    }
+}
+
+impl Drop for GLFixedTable {
+    fn drop(&mut self) {
+        unsafe {
+            bindings::gsl_integration_glfixed_table_free(self.table)
+        }
+    }
+}
+
+/// Fixed-order Gauss-Legendre integration. Applies an `n`-point
+/// Gauss-Legendre rule, which is exact for polynomials of degree `2n - 1`
+/// or less, to the integration range. The table of nodes and weights is
+/// computed once, when the `GLFixed` is created, so this is very cheap to
+/// apply repeatedly, e.g. on many small intervals.
+///
+/// Since the rule is fixed, no error estimate is available; the reported
+/// error is always zero, and the tolerances given to `integrate` are
+/// ignored.
+///
+/// As the nodes are known in advance, `GLFixed` is also a `BatchIntegrator`,
+/// passing all `n` nodes to a `BatchIntegrand` at once.
+///
+/// See GSL docs
+/// [here](https://www.gnu.org/software/gsl/doc/html/integration.html#gauss-legendre-integration).
+///
+/// ```
+/// use integrators::{gsl, Integrator, Real};
+/// let mut glfixed = gsl::GLFixed::new(20);
+///
+/// let mut total = 0.0;
+/// for i in 0..100 {
+///     let low = (i as Real) / 100.0;
+///     glfixed = glfixed.with_range(low, low + 0.01);
+///     total += glfixed.integrate(|x: Real| x.exp(), 0.0, 0.0)
+///                     .unwrap()
+///                     .value;
+/// }
+/// assert!((total - (1f64.exp() - 1.0)).abs() < 1e-12);
+///
+/// let glfixed = glfixed.with_range(0.0, 1.0);
+/// let (x, w) = glfixed.point(0).unwrap();
+/// assert!(0.0 < x && x < 1.0 && w > 0.0);
+/// assert_eq!(glfixed.point(20), None);
+/// ```
+#[derive(Debug, Clone)]
+pub struct GLFixed {
+    range_low: Real,
+    range_high: Real,
+    table: GLFixedTable,
+}
+
+impl GLFixed {
+    /// Creates a new `n`-point Gauss-Legendre rule. This will integrate over
+    /// the range [0, 1]; to change the integration bounds, see `with_range`.
+    ///
+    /// # Panics
+    /// If `n` is zero, panics.
+    pub fn new(n: usize) -> Self {
+        GLFixed {
+            range_low: 0.0,
+            range_high: 1.0,
+            table: GLFixedTable::new(n)
+        }
+    }
+
+    /// Use a different integration range. This does not recompute the
+    /// table. (Default = [0, 1])
+    pub fn with_range(self, range_low: Real, range_high: Real) -> Self {
+        GLFixed { range_low, range_high, ..self }
+    }
+
+    /// The number of points in the rule.
+    pub fn n(&self) -> usize {
+        self.table.n
+    }
+
+    /// Returns the `i`th node and its weight, `(x_i, w_i)`, scaled to the
+    /// current integration range, so that the integral of `f` is the sum of
+    /// `w_i f(x_i)` over all `i`. Returns `None` if `i >= n`.
+    pub fn point(&self, i: usize) -> Option<(Real, Real)> {
+        if i >= self.table.n {
+            return None
+        }
+        let mut x: Real = 0.0;
+        let mut w: Real = 0.0;
+        let retcode = unsafe {
+            bindings::gsl_integration_glfixed_point(self.range_low, self.range_high,
+                                                    i, &mut x, &mut w,
+                                                    self.table.table)
+        };
+        if retcode != bindings::GSL_SUCCESS {
+            None
+        } else {
+            Some((x, w))
+        }
+    }
+}
+
+impl Integrator for GLFixed {
+    type Success = IntegrationResult;
+    type Failure = GSLIntegrationError;
+    fn integrate<A, B, F: FnMut(A) -> B>(&mut self, mut fun: F, _epsrel: Real, _epsabs: Real) -> Result<Self::Success, Self::Failure>
+        where A: IntegrandInput,
+              B: IntegrandOutput
+    {
+        if A::input_size() != 1 {
+            return Err(GSLIntegrationError::InvalidInputDim(A::input_size()))
+        }
+
+        // Sum the rule directly, rather than through GSL, to avoid the setup
+        // of a GSL function on every call; this is often an inner loop
+        let mut value = 0.0;
+        let mut output = [0.0];
+        for i in 0..self.n() {
+            let (x, w) = self.point(i).expect("every node of the table should exist");
+            let out = fun(A::from_args(&[x]));
+            if out.output_size() != 1 {
+                // Multiple outputs are handled by `Componentwise`, one at a time
+                return Err(GSLIntegrationError::InvalidOutputDim(out.output_size()))
+            }
+            out.into_args(&mut output[..]);
+            value += w * output[0];
+        }
+
+        Ok(IntegrationResult {
+            value, error: 0.0
+        })
+    }
+}
+
+impl BatchIntegrator for GLFixed {
+    type Success = Vec<IntegrationResult>;
+    type Failure = GSLIntegrationError;
+    fn integrate_batch<I>(&mut self, mut integrand: I, _epsrel: Real, _epsabs: Real) -> Result<Self::Success, Self::Failure>
+        where I: BatchIntegrand
+    {
+        if integrand.input_size() != 1 {
+            return Err(GSLIntegrationError::InvalidInputDim(integrand.input_size()))
+        }
+        let ncomp = integrand.output_size();
+        if ncomp == 0 {
+            return Err(GSLIntegrationError::InvalidOutputDim(ncomp))
+        }
+
+        let (nodes, weights): (Vec<Real>, Vec<Real>) =
+                (0..self.n()).map(|i| self.point(i).expect("every node of the table should exist"))
+                             .unzip();
+        let mut output = vec![0.0; nodes.len() * ncomp];
+        integrand.evaluate(&nodes[..], &mut output[..]);
+
+        Ok((0..ncomp).map(|comp| {
+            IntegrationResult {
+                value: weights.iter()
+                              .zip(output.chunks(ncomp))
+                              .map(|(w, out)| w * out[comp])
+                              .sum(),
+                error: 0.0
+            }
+        }).collect())
+    }
+}
@@ -0,0 +1,160 @@
+use std::{error, fmt};
+use std::cell::{Cell, RefCell};
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int};
+use std::sync::Mutex;
+
+use ::bindings;
+
+/// The details GSL reports along with an error code: a human-readable
+/// reason, and the location in GSL's source where the error was raised.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GSLErrorReason {
+    pub reason: String,
+    pub file: String,
+    pub line: c_int,
+    /// The raw GSL error code.
+    pub errno: c_int,
+}
+
+impl fmt::Display for GSLErrorReason {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "(GSL) {} ({}:{}, error code {})",
+               self.reason, self.file, self.line, self.errno)
+    }
+}
+
+impl error::Error for GSLErrorReason {}
+
+impl GSLErrorReason {
+    /// The reason for a failure detected in these wrappers, rather than
+    /// reported by GSL, raised at `file` and `line` of this crate.
+    pub(crate) fn internal(reason: &str, errno: c_int, file: &str, line: u32) -> Self {
+        GSLErrorReason {
+            reason: reason.to_string(),
+            file: file.to_string(),
+            line: line as c_int,
+            errno
+        }
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<GSLErrorReason>> = RefCell::new(None);
+    /// The number of live `ErrorHandlerGuard`s on this thread.
+    static THREAD_GUARDS: Cell<usize> = Cell::new(0);
+}
+
+/// The number of live `ErrorHandlerGuard`s, and the error handler which was
+/// installed before the first of them was created.
+static HANDLER_STATE: Mutex<(usize, bindings::gsl_error_handler_t)> = Mutex::new((0, None));
+
+unsafe fn string_from_ptr(ptr: *const c_char) -> String {
+    if ptr.is_null() {
+        String::new()
+    } else {
+        CStr::from_ptr(ptr).to_string_lossy().into_owned()
+    }
+}
+
+unsafe extern "C"
+fn record_error(reason: *const c_char, file: *const c_char, line: c_int, gsl_errno: c_int) {
+    // Never panic across the FFI boundary, even if the thread is exiting
+    let integrating = THREAD_GUARDS.try_with(|guards| guards.get() > 0).unwrap_or(false);
+    if !integrating {
+        return forward_error(reason, file, line, gsl_errno)
+    }
+
+    let err = GSLErrorReason {
+        reason: string_from_ptr(reason),
+        file: string_from_ptr(file),
+        line,
+        errno: gsl_errno
+    };
+    let _ = LAST_ERROR.try_with(|last| *last.borrow_mut() = Some(err));
+}
+
+/// Handles an error raised by GSL on a thread which isn't integrating, with
+/// the handler which was replaced by `record_error`.
+unsafe fn forward_error(reason: *const c_char, file: *const c_char, line: c_int, gsl_errno: c_int) {
+    let previous = match HANDLER_STATE.lock() {
+        Ok(state) => state.1,
+        Err(poisoned) => poisoned.into_inner().1,
+    };
+    match previous {
+        Some(handler) => handler(reason, file, line, gsl_errno),
+        None => {
+            // GSL's default handler was in place, which can't be called
+            // directly; uninstall ours so that GSL falls back to it. It
+            // aborts the process, so this doesn't need to be undone.
+            bindings::gsl_set_error_handler(None);
+            bindings::gsl_error(reason, file, line, gsl_errno)
+        }
+    }
+}
+
+/// Takes the error most recently reported by GSL on this thread, if it was
+/// reported with the error code `errno`.
+pub(crate) fn take_last_error(errno: c_int) -> Option<GSLErrorReason> {
+    LAST_ERROR.with(|last| last.borrow_mut().take())
+              .and_then(|err| if err.errno == errno { Some(err) } else { None })
+}
+
+/// Takes the error most recently reported by GSL on this thread, whatever
+/// its error code, for calls which report failure by other means, such as
+/// returning a null pointer.
+pub(crate) fn take_any_error() -> Option<GSLErrorReason> {
+    LAST_ERROR.with(|last| last.borrow_mut().take())
+}
+
+/// While any of these guards are alive, GSL errors raised on the threads
+/// which hold them are recorded for `take_last_error`, rather than handled by
+/// GSL's default handler (which aborts the process). When the last guard is
+/// dropped, the previous handler is restored.
+///
+/// GSL has a single, process-wide error handler, so this has some limits:
+///
+/// * Errors raised on other threads while a guard is alive are passed to
+///   the handler which was installed before the first guard, so they are
+///   handled as usual. If that was GSL's default handler, it is reinstated
+///   to handle the error, and aborts the process as usual.
+/// * If another handler is installed with `gsl_set_error_handler` while a
+///   guard is alive, it receives the errors raised by integrations too, and
+///   the details of those errors are lost. When the last guard is dropped,
+///   that handler is left in place, rather than replaced with the one saved
+///   before the first guard.
+#[derive(Debug)]
+pub(crate) struct ErrorHandlerGuard(());
+
+impl ErrorHandlerGuard {
+    pub(crate) fn new() -> Self {
+        LAST_ERROR.with(|last| *last.borrow_mut() = None);
+        THREAD_GUARDS.with(|guards| guards.set(guards.get() + 1));
+
+        let mut state = HANDLER_STATE.lock().unwrap_or_else(|e| e.into_inner());
+        if state.0 == 0 {
+            state.1 = unsafe { bindings::gsl_set_error_handler(Some(record_error)) };
+        }
+        state.0 += 1;
+        ErrorHandlerGuard(())
+    }
+}
+
+impl Drop for ErrorHandlerGuard {
+    fn drop(&mut self) {
+        let _ = THREAD_GUARDS.try_with(|guards| guards.set(guards.get() - 1));
+
+        let mut state = HANDLER_STATE.lock().unwrap_or_else(|e| e.into_inner());
+        state.0 -= 1;
+        if state.0 == 0 {
+            unsafe {
+                let current = bindings::gsl_set_error_handler(state.1);
+                // Don't clobber a handler installed since the first guard
+                let ours = record_error as unsafe extern "C" fn(_, _, _, _) as usize;
+                if current.map(|handler| handler as usize) != Some(ours) {
+                    bindings::gsl_set_error_handler(current);
+                }
+            }
+        }
+    }
+}
@@ -5,10 +5,15 @@
 //! module, are gated with the `gsl` feature. So, if you don't want to use
 //! these wrappers, or don't have GSL installed, just turn off that feature.
 //!
-//! Note that GSL's integration routines can only support integration over
-//! one dimension; if you need to integrate over multiple dimensions, you can
-//! nest calls to integrators, or (preferrably) use another library such as
-//! Cuba.
+//! GSL's integrators only accept integrands with a single output, and fail
+//! with `GSLIntegrationError::InvalidOutputDim` otherwise. To integrate each
+//! output of a multi-output integrand, wrap any of them in `Componentwise`.
+//!
+//! Note that GSL's quadrature routines can only support integration over
+//! one dimension. To integrate over multiple dimensions, you can use GSL's
+//! Monte Carlo routines (`MonteCarloPlain`, `Miser`, and `GslVegas`), nest
+//! integrators with `integrators::Nested`, or (preferrably) use another
+//! library such as Cuba.
 //!
 //! ```rust
 //! use integrators::{Integrator, Real};
@@ -41,11 +46,15 @@ use std::os::raw::{c_void, c_int};
 use super::bindings;
 use super::ffi::LandingPad;
 use super::traits::{IntegrandInput, IntegrandOutput};
-use super::Real;
+use super::{IntegrationResult, Real};
 
 #[cfg(test)]
 mod test;
 
+mod handler;
+pub use self::handler::GSLErrorReason;
+use self::handler::ErrorHandlerGuard;
+
 mod qng;
 pub use self::qng::QNG;
 
@@ -61,6 +70,48 @@ pub use self::qagp::QAGP;
 mod qagi;
 pub use self::qagi::{QAGI, QAGIU, QAGIL};
 
+mod qawo;
+pub use self::qawo::{QAWO, QAWOWeight};
+
+mod qawf;
+pub use self::qawf::QAWF;
+
+mod qawc;
+pub use self::qawc::QAWC;
+
+mod qaws;
+pub use self::qaws::{QAWS, QAWSTable};
+
+mod cquad;
+pub use self::cquad::{CQUAD, CQUADResult};
+
+// Only available with GSL 2.5 or newer; see build.rs
+#[cfg(gsl_romberg)]
+mod romberg;
+#[cfg(gsl_romberg)]
+pub use self::romberg::{Romberg, RombergResult};
+
+// Only available with GSL 2.3 or newer; see build.rs
+#[cfg(gsl_fixed)]
+mod fixed;
+#[cfg(gsl_fixed)]
+pub use self::fixed::{FixedQuadrature, FixedWeight};
+
+mod glfixed;
+pub use self::glfixed::GLFixed;
+
+mod qk;
+pub use self::qk::{GaussKronrodRule, GaussKronrodResult};
+
+mod componentwise;
+pub use self::componentwise::Componentwise;
+
+mod rng;
+pub use self::rng::{Rng, RngType};
+
+mod monte;
+pub use self::monte::{MonteCarloPlain, Miser, GslVegas, MonteCarloResult};
+
 unsafe extern "C"
 fn gsl_integrand_fn<A, B, F>(x: Real, params: *mut c_void) -> Real
     where A: IntegrandInput,
@@ -84,6 +135,7 @@ fn gsl_integrand_fn<A, B, F>(x: Real, params: *mut c_void) -> Real
 #[derive(Debug)]
 struct GSLFunction<'a> {
     function: bindings::gsl_function,
+    _handler: ErrorHandlerGuard,
     lifetime: marker::PhantomData<&'a ()>
 }
 
@@ -93,14 +145,11 @@ fn make_gsl_function<'a, A, B, F>(fun: &'a mut LandingPad<A, B, F>, range_low: R
           B: IntegrandOutput,
           F: FnMut(A) -> B
 {
-    // Disable the default error handler so we can handle GSL errors in Rust
-    // Otherwise - GSL would use default behavior of aborting process on error
-    unsafe { bindings::gsl_set_error_handler_off() };
-
     if A::input_size() != 1 {
         Err(GSLIntegrationError::InvalidInputDim(A::input_size()))
     } else if fun.raw_call(&[(range_low + range_high) / 2f64])
                  .output_size() != 1 {
+        // Multiple outputs are handled by `Componentwise`, one at a time
         let output_size = fun.raw_call(&[(range_low + range_high) / 2f64])
                              .output_size();
         Err(GSLIntegrationError::InvalidOutputDim(output_size))
@@ -110,6 +159,10 @@ fn make_gsl_function<'a, A, B, F>(fun: &'a mut LandingPad<A, B, F>, range_low: R
                 function: Some(gsl_integrand_fn::<A, B, F>),
                 params: unsafe { mem::transmute(fun) }
             },
+            // Replace the default error handler while the function is in use,
+            // so we can handle GSL errors in Rust. Otherwise - GSL would use
+            // default behavior of aborting process on error
+            _handler: ErrorHandlerGuard::new(),
             lifetime: marker::PhantomData
         })
     }
@@ -195,11 +248,33 @@ impl Into<c_int> for GSLErrorCode {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// The ways a GSL integration can fail.
+///
+/// Some variants carry `Real` values (the location of a pole, or a partial
+/// result), so this error implements `PartialEq`, but no longer `Eq`.
+#[derive(Debug, Clone, PartialEq)]
 pub enum GSLIntegrationError {
     InvalidInputDim(usize),
+    /// The integrand has the given number of outputs, rather than one. To
+    /// integrate several outputs at once, wrap the integrator in
+    /// `Componentwise`.
     InvalidOutputDim(usize),
-    GSLError(GSLErrorCode),
+    /// The pole of a Cauchy principal value integral lies on an endpoint of
+    /// the integration range. The location of the pole is given.
+    PoleAtEndpoint(Real),
+    /// GSL returned an error code. The details GSL reported along with it
+    /// are provided, when available.
+    GSLError(GSLErrorCode, Option<GSLErrorReason>),
+    /// An adaptive integrator ran out of subintervals (`MaxIter`), or could
+    /// not converge due to round-off error (`Round`). The best estimate GSL
+    /// reached is still provided, along with the number of subintervals it
+    /// used, so that it can be accepted if it is good enough, and the
+    /// details GSL reported, when available.
+    ///
+    /// The Monte Carlo integrators also fail this way (with `MaxIter`) when
+    /// they exhaust `maxcalls`, giving the number of integrand evaluations
+    /// used instead.
+    DidNotConverge(GSLErrorCode, IntegrationResult, usize, Option<GSLErrorReason>),
 }
 
 impl fmt::Display for GSLIntegrationError {
@@ -207,8 +282,12 @@ impl fmt::Display for GSLIntegrationError {
         use self::GSLIntegrationError::*;
         match &self {
             &InvalidInputDim(n) => write!(fmt, "(GSL) Invalid input dim: {}", n),
-            &InvalidOutputDim(n) => write!(fmt, "(GSL) Invalid output dim: {}", n),
-            &GSLError(err) => write!(fmt, "{}", err)
+            &InvalidOutputDim(n) =>
+                write!(fmt, "(GSL) Invalid output dim: {} (see gsl::Componentwise for multiple outputs)", n),
+            &PoleAtEndpoint(c) => write!(fmt, "(GSL) Pole at integration endpoint: {}", c),
+            &GSLError(err, _) => write!(fmt, "{}", err),
+            &DidNotConverge(err, res, _, _) =>
+                write!(fmt, "{} (partial result: {} +/- {})", err, res.value, res.error),
         }
     }
 }
@@ -216,12 +295,23 @@ impl fmt::Display for GSLIntegrationError {
 impl error::Error for GSLIntegrationError {
     fn source(&self) -> Option<&(error::Error + 'static)> {
         match &self {
-            &GSLIntegrationError::GSLError(ref err) => Some(err),
+            &GSLIntegrationError::GSLError(_, Some(ref reason))
+                | &GSLIntegrationError::DidNotConverge(_, _, _, Some(ref reason)) => Some(reason),
+            &GSLIntegrationError::GSLError(ref err, None)
+                | &GSLIntegrationError::DidNotConverge(ref err, _, _, None) => Some(err),
             _ => None,
         }
     }
 }
 
+impl GSLIntegrationError {
+    /// Builds the error for a failed GSL call which returned `retcode`,
+    /// along with the details GSL reported for it.
+    pub(crate) fn from_retcode(retcode: c_int) -> Self {
+        GSLIntegrationError::GSLError(retcode.into(), handler::take_last_error(retcode))
+    }
+}
+
 pub type GSLResult<T> = Result<T, GSLIntegrationError>;
 
 #[derive(Debug, Copy, Clone, PartialEq)]
@@ -230,6 +320,22 @@ pub struct GSLIntegrationResult {
     pub error: Real
 }
 
+/// One of the subintervals into which an adaptive integrator divided the
+/// integration range. Each adaptive integrator's `subintervals` method
+/// returns those of its most recent integration, which is empty if nothing
+/// has been integrated yet.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Subinterval {
+    pub low: Real,
+    pub high: Real,
+    /// The integral over this subinterval.
+    pub value: Real,
+    /// The error estimate of the integral over this subinterval.
+    pub error: Real,
+    /// The number of bisections which produced this subinterval.
+    pub level: usize,
+}
+
 struct GSLIntegrationWorkspace {
     pub(crate) nintervals: usize,
     wkspc: *mut bindings::gsl_integration_workspace
@@ -259,6 +365,44 @@ impl GSLIntegrationWorkspace {
             nintervals: n
         }
     }
+
+    /// Converts the return code and results of an adaptive integration using
+    /// this workspace into a `GSLResult`, keeping the partial results if
+    /// integration did not converge.
+    pub(crate) fn integration_result(&self, retcode: c_int, value: Real, error: Real)
+            -> GSLResult<IntegrationResult> {
+        if retcode == bindings::GSL_SUCCESS {
+            return Ok(IntegrationResult {
+                value, error
+            })
+        }
+        match retcode.into() {
+            code @ GSLErrorCode::MaxIter | code @ GSLErrorCode::Round => {
+                let nintervals = unsafe { (*self.wkspc).size };
+                Err(GSLIntegrationError::DidNotConverge(code, IntegrationResult {
+                    value, error
+                }, nintervals, handler::take_last_error(retcode)))
+            },
+            _ => Err(GSLIntegrationError::from_retcode(retcode))
+        }
+    }
+
+    /// The subintervals left in the workspace by the most recent
+    /// integration, with their contributions to the integral and error
+    /// estimates. Empty if nothing has been integrated yet. The adaptive
+    /// integrators' `subintervals` methods all delegate here.
+    pub(crate) fn subintervals(&self) -> Vec<Subinterval> {
+        let wkspc = unsafe { &*self.wkspc };
+        (0..wkspc.size).map(|i| unsafe {
+            Subinterval {
+                low: *wkspc.alist.offset(i as isize),
+                high: *wkspc.blist.offset(i as isize),
+                value: *wkspc.rlist.offset(i as isize),
+                error: *wkspc.elist.offset(i as isize),
+                level: *wkspc.level.offset(i as isize),
+            }
+        }).collect()
+    }
 }
 
 impl Drop for GSLIntegrationWorkspace {
@@ -0,0 +1,526 @@
+use std::{fmt, marker, mem, slice};
+use std::os::raw::{c_int, c_void};
+
+use ::bindings;
+use ::{IntegrationResult, IntegrationResultIter, Integrator, Real};
+use ::ffi::LandingPad;
+use ::traits::{IntegrandInput, IntegrandOutput, IntegrationResults};
+
+use super::{ErrorHandlerGuard, GSLErrorCode, GSLErrorReason, GSLIntegrationError, GSLResult, Rng};
+
+unsafe extern "C"
+fn gsl_monte_integrand_fn<A, B, F>(x: *mut Real, dim: usize, params: *mut c_void) -> Real
+    where A: IntegrandInput,
+          B: IntegrandOutput,
+          F: FnMut(A) -> B
+{
+    let fnptr = params as *mut LandingPad<A, B, F>;
+    let fun: &mut LandingPad<A, B, F> = &mut *fnptr;
+
+    let args = slice::from_raw_parts(x, dim);
+    let mut output: [Real; 1] = [0.0];
+    match fun.try_call(args, &mut output) {
+        Ok(_) => output[0],
+        Err(_) => 0.0,
+    }
+}
+
+#[derive(Debug)]
+struct GSLMonteFunction<'a> {
+    function: bindings::gsl_monte_function,
+    _handler: ErrorHandlerGuard,
+    lifetime: marker::PhantomData<&'a ()>
+}
+
+fn make_gsl_monte_function<'a, A, B, F>(fun: &'a mut LandingPad<A, B, F>, xl: &[Real], xu: &[Real])
+        -> GSLResult<GSLMonteFunction<'a>>
+    where A: IntegrandInput,
+          B: IntegrandOutput,
+          F: FnMut(A) -> B
+{
+    let center = xl.iter().zip(xu.iter())
+                   .map(|(&l, &u)| (l + u) / 2f64)
+                   .collect::<Vec<_>>();
+    let output_size = fun.raw_call(&center[..]).output_size();
+    if output_size != 1 {
+        Err(GSLIntegrationError::InvalidOutputDim(output_size))
+    } else {
+        Ok(GSLMonteFunction {
+            function: bindings::gsl_monte_function {
+                f: Some(gsl_monte_integrand_fn::<A, B, F>),
+                dim: xl.len(),
+                params: unsafe { mem::transmute(fun) }
+            },
+            // As with make_gsl_function, so GSL errors are handled in Rust
+            _handler: ErrorHandlerGuard::new(),
+            lifetime: marker::PhantomData
+        })
+    }
+}
+
+/// The result of a Monte Carlo integration, along with the number of
+/// integrand evaluations it used.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct MonteCarloResult {
+    pub value: Real,
+    pub error: Real,
+    /// The number of integrand evaluations used.
+    pub neval: usize,
+}
+
+impl IntegrationResults for MonteCarloResult {
+    type Iterator = IntegrationResultIter;
+    fn results(self) -> Self::Iterator {
+        IntegrationResult {
+            value: self.value,
+            error: self.error,
+        }.results()
+    }
+}
+
+/// Finds the lower and upper bounds of integration for an integrand with
+/// `ndim` inputs. An empty `range` means the unit hypercube.
+fn integration_bounds(range: &[(Real, Real)], ndim: usize) -> GSLResult<(Vec<Real>, Vec<Real>)> {
+    if ndim == 0 {
+        Err(GSLIntegrationError::InvalidInputDim(ndim))
+    } else if range.is_empty() {
+        Ok((vec![0.0; ndim], vec![1.0; ndim]))
+    } else if range.len() != ndim {
+        Err(GSLIntegrationError::InvalidInputDim(ndim))
+    } else {
+        Ok(range.iter().cloned().unzip())
+    }
+}
+
+/// Sets up the integrand for one of GSL's Monte Carlo routines, and hands it
+/// to `run` along with the lower and upper bounds of integration.
+fn monte_integrate<A, B, F, G>(range: &[(Real, Real)], fun: F, run: G) -> GSLResult<MonteCarloResult>
+    where A: IntegrandInput,
+          B: IntegrandOutput,
+          F: FnMut(A) -> B,
+          G: FnOnce(&mut bindings::gsl_monte_function, &mut [Real], &mut [Real]) -> GSLResult<MonteCarloResult>
+{
+    let (mut xl, mut xu) = integration_bounds(range, A::input_size())?;
+
+    let mut lp = LandingPad::new(fun);
+    let res = {
+        let mut gslfn = make_gsl_monte_function(&mut lp, &xl[..], &xu[..])?;
+        run(&mut gslfn.function, &mut xl[..], &mut xu[..])
+    };
+    lp.maybe_resume_unwind();
+    res
+}
+
+macro_rules! monte_state {
+    ($name:ident, $state:ty, $alloc:path, $init:path, $free:path) => {
+        struct $name {
+            dim: usize,
+            state: *mut $state
+        }
+
+        impl fmt::Debug for $name {
+            fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+                fmt.debug_struct(stringify!($name))
+                   .field("dim", &self.dim)
+                   .finish()
+            }
+        }
+
+        impl Clone for $name {
+            fn clone(&self) -> Self {
+                $name::new(self.dim)
+            }
+        }
+
+        impl $name {
+            fn new(dim: usize) -> Self {
+                $name {
+                    // TODO: Check for null-pointer
+                    state: unsafe { $alloc(dim) },
+                    dim
+                }
+            }
+
+            /// Makes sure `state` holds a freshly initialized state for
+            /// integrating over `dim` dimensions.
+            fn prepare(state: &mut Option<Self>, dim: usize) -> *mut $state {
+                if state.as_ref().map(|s| s.dim != dim).unwrap_or(true) {
+                    *state = Some($name::new(dim));
+                }
+                let state = state.as_ref().expect("just set to Some(..)").state;
+                unsafe { $init(state) };
+                state
+            }
+        }
+
+        impl Drop for $name {
+            fn drop(&mut self) {
+                unsafe {
+                    $free(self.state)
+                }
+            }
+        }
+    }
+}
+
+monte_state!(PlainState, bindings::gsl_monte_plain_state,
+             bindings::gsl_monte_plain_alloc,
+             bindings::gsl_monte_plain_init,
+             bindings::gsl_monte_plain_free);
+monte_state!(MiserState, bindings::gsl_monte_miser_state,
+             bindings::gsl_monte_miser_alloc,
+             bindings::gsl_monte_miser_init,
+             bindings::gsl_monte_miser_free);
+monte_state!(VegasState, bindings::gsl_monte_vegas_state,
+             bindings::gsl_monte_vegas_alloc,
+             bindings::gsl_monte_vegas_init,
+             bindings::gsl_monte_vegas_free);
+
+/// The reason a Monte Carlo integration failed once `maxcalls` ran out, at
+/// `line` of this file.
+fn maxcalls_reason(line: u32) -> GSLErrorReason {
+    GSLErrorReason::internal("exceeded maxcalls without reaching the requested tolerance",
+                             bindings::GSL_EMAXITER, file!(), line)
+}
+
+/// Runs batches of `calls` evaluations with `run`, until the average of the
+/// batches reaches the requested tolerance, or `maxcalls` would be exceeded.
+fn integrate_independent_batches<G>(calls: usize, maxcalls: usize, epsrel: Real, epsabs: Real, mut run: G)
+        -> GSLResult<MonteCarloResult>
+    where G: FnMut(&mut Real, &mut Real) -> c_int
+{
+    let mut nbatches: usize = 0;
+    let mut value_sum: Real = 0.0;
+    let mut variance_sum: Real = 0.0;
+    loop {
+        let mut value: Real = 0.0;
+        let mut error: Real = 0.0;
+        let retcode = run(&mut value, &mut error);
+        if retcode != bindings::GSL_SUCCESS {
+            return Err(GSLIntegrationError::from_retcode(retcode))
+        }
+
+        nbatches += 1;
+        value_sum += value;
+        variance_sum += error * error;
+
+        let value = value_sum / nbatches as Real;
+        let error = variance_sum.sqrt() / nbatches as Real;
+        if error <= epsabs.max(epsrel * value.abs()) {
+            return Ok(MonteCarloResult {
+                value, error, neval: nbatches * calls
+            })
+        } else if (nbatches + 1) * calls > maxcalls {
+            return Err(GSLIntegrationError::DidNotConverge(GSLErrorCode::MaxIter, IntegrationResult {
+                value, error
+            }, nbatches * calls, Some(maxcalls_reason(line!()))))
+        }
+    }
+}
+
+/// Plain Monte Carlo integration, which samples the integrand uniformly over
+/// the integration region. Integrates over any number of dimensions.
+///
+/// The integrand is sampled in batches of `calls` points. The batches are
+/// averaged until the requested tolerance is reached; if it is not reached
+/// before a total of `maxcalls` points is sampled, integration fails.
+///
+/// See GSL docs
+/// [here](https://www.gnu.org/software/gsl/doc/html/montecarlo.html#plain-monte-carlo).
+///
+/// ```
+/// use integrators::{gsl, Integrator, Real};
+/// let mut plain = gsl::MonteCarloPlain::new(100000)
+///                                      .with_maxcalls(10000000)
+///                                      .with_range(vec![(0.0, 2.0), (0.0, 3.0)]);
+///
+/// let res = plain.integrate(|(x, y): (Real, Real)| x * y, 1e-2, 0.0)
+///                .unwrap();
+/// assert!((res.value - 9.0).abs() < 5.0 * res.error);
+/// ```
+#[derive(Debug, Clone)]
+pub struct MonteCarloPlain {
+    range: Vec<(Real, Real)>,
+    calls: usize,
+    maxcalls: usize,
+    state: Option<PlainState>,
+    rng: Rng,
+}
+
+impl MonteCarloPlain {
+    /// Creates a new integrator which samples `calls` points per batch, and
+    /// only one batch in total; see `with_maxcalls` to allow more. It will
+    /// integrate over the unit hypercube; see `with_range` to change that.
+    pub fn new(calls: usize) -> Self {
+        MonteCarloPlain {
+            range: Vec::new(),
+            calls,
+            maxcalls: calls,
+            state: None,
+            rng: Rng::default(),
+        }
+    }
+
+    /// Sample `calls` points per batch.
+    pub fn with_calls(self, calls: usize) -> Self {
+        MonteCarloPlain { calls, ..self }
+    }
+
+    /// Sample at most `maxcalls` points in total.
+    pub fn with_maxcalls(self, maxcalls: usize) -> Self {
+        MonteCarloPlain { maxcalls, ..self }
+    }
+
+    /// Integrate over the hyperrectangle with the given `(lower, upper)`
+    /// bounds in each dimension. An empty range means the unit hypercube.
+    /// (Default = unit hypercube)
+    pub fn with_range<I>(self, range: I) -> Self
+        where I: IntoIterator<Item=(Real, Real)> {
+        MonteCarloPlain {
+            range: range.into_iter().collect(), ..self
+        }
+    }
+
+    /// Sample points with the given random number generator. (Default = a
+    /// Mersenne Twister with GSL's default seed)
+    pub fn with_rng(self, rng: Rng) -> Self {
+        MonteCarloPlain { rng, ..self }
+    }
+
+    /// The random number generator, in its state after the most recent
+    /// integration.
+    pub fn rng(&self) -> &Rng {
+        &self.rng
+    }
+}
+
+impl Integrator for MonteCarloPlain {
+    type Success = MonteCarloResult;
+    type Failure = GSLIntegrationError;
+    fn integrate<A, B, F: FnMut(A) -> B>(&mut self, fun: F, epsrel: Real, epsabs: Real) -> Result<Self::Success, Self::Failure>
+        where A: IntegrandInput,
+              B: IntegrandOutput
+    {
+        let MonteCarloPlain { ref range, calls, maxcalls, ref mut state, ref mut rng } = *self;
+        monte_integrate(&range[..], fun, |gslfn, xl, xu| {
+            let state = PlainState::prepare(state, xl.len());
+            integrate_independent_batches(calls, maxcalls, epsrel, epsabs, |value, error| unsafe {
+                bindings::gsl_monte_plain_integrate(&mut *gslfn,
+                                                    xl.as_mut_ptr(), xu.as_mut_ptr(),
+                                                    xl.len(), calls,
+                                                    rng.as_ptr(), state,
+                                                    value, error)
+            })
+        })
+    }
+}
+
+/// The MISER algorithm of Press and Farrar, which uses recursive stratified
+/// sampling to concentrate samples in the regions where the variance of the
+/// integrand is largest. Integrates over any number of dimensions.
+///
+/// As with `MonteCarloPlain`, the integrand is sampled in batches of `calls`
+/// points, which are averaged until the requested tolerance is reached, or
+/// integration fails once `maxcalls` points have been sampled.
+///
+/// See GSL docs
+/// [here](https://www.gnu.org/software/gsl/doc/html/montecarlo.html#miser).
+#[derive(Debug, Clone)]
+pub struct Miser {
+    range: Vec<(Real, Real)>,
+    calls: usize,
+    maxcalls: usize,
+    state: Option<MiserState>,
+    rng: Rng,
+}
+
+impl Miser {
+    /// Creates a new integrator which samples `calls` points per batch, and
+    /// only one batch in total; see `with_maxcalls` to allow more. It will
+    /// integrate over the unit hypercube; see `with_range` to change that.
+    pub fn new(calls: usize) -> Self {
+        Miser {
+            range: Vec::new(),
+            calls,
+            maxcalls: calls,
+            state: None,
+            rng: Rng::default(),
+        }
+    }
+
+    /// Sample `calls` points per batch.
+    pub fn with_calls(self, calls: usize) -> Self {
+        Miser { calls, ..self }
+    }
+
+    /// Sample at most `maxcalls` points in total.
+    pub fn with_maxcalls(self, maxcalls: usize) -> Self {
+        Miser { maxcalls, ..self }
+    }
+
+    /// Integrate over the hyperrectangle with the given `(lower, upper)`
+    /// bounds in each dimension. An empty range means the unit hypercube.
+    /// (Default = unit hypercube)
+    pub fn with_range<I>(self, range: I) -> Self
+        where I: IntoIterator<Item=(Real, Real)> {
+        Miser {
+            range: range.into_iter().collect(), ..self
+        }
+    }
+
+    /// Sample points with the given random number generator. (Default = a
+    /// Mersenne Twister with GSL's default seed)
+    pub fn with_rng(self, rng: Rng) -> Self {
+        Miser { rng, ..self }
+    }
+
+    /// The random number generator, in its state after the most recent
+    /// integration.
+    pub fn rng(&self) -> &Rng {
+        &self.rng
+    }
+}
+
+impl Integrator for Miser {
+    type Success = MonteCarloResult;
+    type Failure = GSLIntegrationError;
+    fn integrate<A, B, F: FnMut(A) -> B>(&mut self, fun: F, epsrel: Real, epsabs: Real) -> Result<Self::Success, Self::Failure>
+        where A: IntegrandInput,
+              B: IntegrandOutput
+    {
+        let Miser { ref range, calls, maxcalls, ref mut state, ref mut rng } = *self;
+        monte_integrate(&range[..], fun, |gslfn, xl, xu| {
+            let state = MiserState::prepare(state, xl.len());
+            integrate_independent_batches(calls, maxcalls, epsrel, epsabs, |value, error| unsafe {
+                bindings::gsl_monte_miser_integrate(&mut *gslfn,
+                                                    xl.as_mut_ptr(), xu.as_mut_ptr(),
+                                                    xl.len(), calls,
+                                                    rng.as_ptr(), state,
+                                                    value, error)
+            })
+        })
+    }
+}
+
+/// GSL's implementation of the VEGAS algorithm of Lepage, which samples
+/// points from an adaptive grid approximating the integrand's distribution.
+/// Integrates over any number of dimensions.
+///
+/// Each batch of `calls` points refines the grid left by the batches before
+/// it, but only the estimate of the latest batch is kept. Integration
+/// succeeds once that estimate reaches the requested tolerance. If that
+/// doesn't happen before `maxcalls` points have been sampled, integration
+/// fails, with the latest estimate. Whether the batches were consistent with
+/// each other is left to the caller to judge, from `chisq`.
+///
+/// This is unrelated to Cuba's Vegas, `cuba::Vegas`.
+///
+/// See GSL docs
+/// [here](https://www.gnu.org/software/gsl/doc/html/montecarlo.html#vegas).
+#[derive(Debug, Clone)]
+pub struct GslVegas {
+    range: Vec<(Real, Real)>,
+    calls: usize,
+    maxcalls: usize,
+    state: Option<VegasState>,
+    rng: Rng,
+}
+
+impl GslVegas {
+    /// Creates a new integrator which samples `calls` points per batch, and
+    /// at most 100 batches in total; see `with_maxcalls` to change that. It
+    /// will integrate over the unit hypercube; see `with_range` to change
+    /// that.
+    pub fn new(calls: usize) -> Self {
+        GslVegas {
+            range: Vec::new(),
+            calls,
+            maxcalls: 100 * calls,
+            state: None,
+            rng: Rng::default(),
+        }
+    }
+
+    /// Sample `calls` points per batch.
+    pub fn with_calls(self, calls: usize) -> Self {
+        GslVegas { calls, ..self }
+    }
+
+    /// Sample at most `maxcalls` points in total.
+    pub fn with_maxcalls(self, maxcalls: usize) -> Self {
+        GslVegas { maxcalls, ..self }
+    }
+
+    /// Integrate over the hyperrectangle with the given `(lower, upper)`
+    /// bounds in each dimension. An empty range means the unit hypercube.
+    /// (Default = unit hypercube)
+    pub fn with_range<I>(self, range: I) -> Self
+        where I: IntoIterator<Item=(Real, Real)> {
+        GslVegas {
+            range: range.into_iter().collect(), ..self
+        }
+    }
+
+    /// Sample points with the given random number generator. (Default = a
+    /// Mersenne Twister with GSL's default seed)
+    pub fn with_rng(self, rng: Rng) -> Self {
+        GslVegas { rng, ..self }
+    }
+
+    /// The random number generator, in its state after the most recent
+    /// integration.
+    pub fn rng(&self) -> &Rng {
+        &self.rng
+    }
+
+    /// The chi-squared per degree of freedom of the batches of the most
+    /// recent integration, or `None` if nothing has been integrated yet.
+    pub fn chisq(&self) -> Option<Real> {
+        self.state.as_ref().map(|state| unsafe {
+            bindings::gsl_monte_vegas_chisq(state.state)
+        })
+    }
+}
+
+impl Integrator for GslVegas {
+    type Success = MonteCarloResult;
+    type Failure = GSLIntegrationError;
+    fn integrate<A, B, F: FnMut(A) -> B>(&mut self, fun: F, epsrel: Real, epsabs: Real) -> Result<Self::Success, Self::Failure>
+        where A: IntegrandInput,
+              B: IntegrandOutput
+    {
+        let GslVegas { ref range, calls, maxcalls, ref mut state, ref mut rng } = *self;
+        monte_integrate(&range[..], fun, |gslfn, xl, xu| {
+            let state = VegasState::prepare(state, xl.len());
+            let mut neval: usize = 0;
+            loop {
+                // Each call keeps refining the grid left by the previous one,
+                // but GSL discards the earlier calls' weighted average, so
+                // `value` and `error` come from this call alone
+                let mut value: Real = 0.0;
+                let mut error: Real = 0.0;
+                let retcode = unsafe {
+                    bindings::gsl_monte_vegas_integrate(&mut *gslfn,
+                                                        xl.as_mut_ptr(), xu.as_mut_ptr(),
+                                                        xl.len(), calls,
+                                                        rng.as_ptr(), state,
+                                                        &mut value, &mut error)
+                };
+                if retcode != bindings::GSL_SUCCESS {
+                    return Err(GSLIntegrationError::from_retcode(retcode))
+                }
+                neval += calls;
+
+                if error <= epsabs.max(epsrel * value.abs()) {
+                    return Ok(MonteCarloResult {
+                        value, error, neval
+                    })
+                } else if neval + calls > maxcalls {
+                    return Err(GSLIntegrationError::DidNotConverge(GSLErrorCode::MaxIter, IntegrationResult {
+                        value, error
+                    }, neval, Some(maxcalls_reason(line!()))))
+                }
+            }
+        })
+    }
+}
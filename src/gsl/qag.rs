@@ -6,7 +6,7 @@ use ::{IntegrationResult, Integrator, Real};
 use ::ffi::LandingPad;
 use ::traits::{IntegrandInput, IntegrandOutput};
 
-use super::{make_gsl_function, GSLIntegrationError, GSLIntegrationWorkspace};
+use super::{make_gsl_function, GSLIntegrationError, GSLIntegrationWorkspace, Subinterval};
 
 /// Quadrature rule to apply for QAG integration. Rules are supported for 15,
 /// 21, 31, 41, 51, 61 points.
@@ -85,6 +85,11 @@ impl QAG {
         }
     }
 
+    /// The subintervals of the most recent integration; see `Subinterval`.
+    pub fn subintervals(&self) -> Vec<Subinterval> {
+        self.wkspc.subintervals()
+    }
+
     /// Use a different integration range. (Default = [0, 1])
     pub fn with_range(self, range_low: Real, range_high: Real) -> Self {
         QAG { range_low, range_high, ..self }
@@ -121,12 +126,6 @@ impl Integrator for QAG {
         };
         lp.maybe_resume_unwind();
 
-        if retcode != bindings::GSL_SUCCESS {
-            Err(GSLIntegrationError::GSLError(retcode.into()))
-        } else {
-            Ok(IntegrationResult {
-                value, error
-            })
-        }
+        self.wkspc.integration_result(retcode, value, error)
     }
 }
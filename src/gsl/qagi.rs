@@ -1,11 +1,9 @@
-use std::convert::Into;
-
 use ::bindings;
 use ::{IntegrationResult, Integrator, Real};
 use ::ffi::LandingPad;
 use ::traits::{IntegrandInput, IntegrandOutput};
 
-use super::{make_gsl_function, GSLIntegrationError, GSLIntegrationWorkspace};
+use super::{make_gsl_function, GSLIntegrationError, GSLIntegrationWorkspace, Subinterval};
 
 /// Quadrature Adaptive General integration for Infinite intervals. It applies
 /// the QAGS algorithm to a transformation of the input integral, such that
@@ -51,6 +49,12 @@ impl QAGI {
             ..self
         }
     }
+
+    /// The subintervals of the most recent integration, in GSL's transformed
+    /// variable `t` in `(0, 1]`; see `Subinterval`.
+    pub fn subintervals(&self) -> Vec<Subinterval> {
+        self.wkspc.subintervals()
+    }
 }
 
 impl QAGIU {
@@ -71,6 +75,12 @@ impl QAGIU {
             ..self
         }
     }
+
+    /// The subintervals of the most recent integration, in GSL's transformed
+    /// variable `t` in `(0, 1]`; see `Subinterval`.
+    pub fn subintervals(&self) -> Vec<Subinterval> {
+        self.wkspc.subintervals()
+    }
 }
 
 impl QAGIL {
@@ -91,6 +101,12 @@ impl QAGIL {
             ..self
         }
     }
+
+    /// The subintervals of the most recent integration, in GSL's transformed
+    /// variable `t` in `(0, 1]`; see `Subinterval`.
+    pub fn subintervals(&self) -> Vec<Subinterval> {
+        self.wkspc.subintervals()
+    }
 }
 
 impl Integrator for QAGI {
@@ -115,13 +131,7 @@ impl Integrator for QAGI {
         };
         lp.maybe_resume_unwind();
 
-        if retcode != bindings::GSL_SUCCESS {
-            Err(GSLIntegrationError::GSLError(retcode.into()))
-        } else {
-            Ok(IntegrationResult {
-                value, error
-            })
-        }
+        self.wkspc.integration_result(retcode, value, error)
     }
 }
 
@@ -150,13 +160,7 @@ impl Integrator for QAGIU {
         };
         lp.maybe_resume_unwind();
 
-        if retcode != bindings::GSL_SUCCESS {
-            Err(GSLIntegrationError::GSLError(retcode.into()))
-        } else {
-            Ok(IntegrationResult {
-                value, error
-            })
-        }
+        self.wkspc.integration_result(retcode, value, error)
     }
 }
 
@@ -185,12 +189,6 @@ impl Integrator for QAGIL {
         };
         lp.maybe_resume_unwind();
 
-        if retcode != bindings::GSL_SUCCESS {
-            Err(GSLIntegrationError::GSLError(retcode.into()))
-        } else {
-            Ok(IntegrationResult {
-                value, error
-            })
-        }
+        self.wkspc.integration_result(retcode, value, error)
     }
 }
@@ -5,7 +5,7 @@ use ::{IntegrationResult, Integrator, Real};
 use ::ffi::LandingPad;
 use ::traits::{IntegrandInput, IntegrandOutput};
 
-use super::{make_gsl_function, GSLIntegrationError, GSLIntegrationWorkspace};
+use super::{make_gsl_function, GSLIntegrationError, GSLIntegrationWorkspace, Subinterval};
 
 /// Quadrature Adaptive General integration with known singular Points.
 /// QAGP applies the same adaptive algorithm as QAGS, with the benefit of known
@@ -61,6 +61,11 @@ impl QAGP {
         }
     }
 
+    /// The subintervals of the most recent integration; see `Subinterval`.
+    pub fn subintervals(&self) -> Vec<Subinterval> {
+        self.wkspc.subintervals()
+    }
+
     /// Provides new singular points. As with `QAGP::new()`, returns `None` if
     /// there are less than 2 points, or if the singular points are not in
     /// ascending order.
@@ -104,12 +109,6 @@ impl Integrator for QAGP {
         };
         lp.maybe_resume_unwind();
 
-        if retcode != bindings::GSL_SUCCESS {
-            Err(GSLIntegrationError::GSLError(retcode.into()))
-        } else {
-            Ok(IntegrationResult {
-                value, error
-            })
-        }
+        self.wkspc.integration_result(retcode, value, error)
     }
 }
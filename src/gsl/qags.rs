@@ -3,7 +3,7 @@ use ::{IntegrationResult, Integrator, Real};
 use ::ffi::LandingPad;
 use ::traits::{IntegrandInput, IntegrandOutput};
 
-use super::{make_gsl_function, GSLIntegrationError, GSLIntegrationWorkspace};
+use super::{make_gsl_function, GSLIntegrationError, GSLIntegrationWorkspace, Subinterval};
 
 /// Quadrature Adaptive General integration with Singularities. Concentrates
 /// subintervals around integrable singularities which converge to the solution,
@@ -36,6 +36,11 @@ impl QAGS {
         }
     }
 
+    /// The subintervals of the most recent integration; see `Subinterval`.
+    pub fn subintervals(&self) -> Vec<Subinterval> {
+        self.wkspc.subintervals()
+    }
+
     pub fn with_range(self, range_low: Real, range_high: Real) -> Self {
         QAGS { range_low, range_high, ..self }
     }
@@ -64,12 +69,6 @@ impl Integrator for QAGS {
         };
         lp.maybe_resume_unwind();
 
-        if retcode != bindings::GSL_SUCCESS {
-            Err(GSLIntegrationError::GSLError(retcode.into()))
-        } else {
-            Ok(IntegrationResult {
-                value, error
-            })
-        }
+        self.wkspc.integration_result(retcode, value, error)
     }
 }
@@ -0,0 +1,110 @@
+use ::bindings;
+use ::{IntegrationResult, Integrator, Real};
+use ::ffi::LandingPad;
+use ::traits::{IntegrandInput, IntegrandOutput};
+
+use super::{make_gsl_function, GSLIntegrationError, GSLIntegrationWorkspace, Subinterval};
+
+/// Quadrature Adaptive integration with Weight function for Cauchy principal
+/// values. Computes the principal value of the integral of `f(x) / (x - c)`,
+/// for a pole at `c`, using the QAG algorithm with modified Clenshaw-Curtis
+/// rules on subintervals containing the pole.
+///
+/// The pole may not lie at either endpoint of the integration range; if it
+/// does, integration fails with `GSLIntegrationError::PoleAtEndpoint`.
+///
+/// See GSL docs
+/// [here](https://www.gnu.org/software/gsl/doc/html/integration.html#qawc-adaptive-integration-for-cauchy-principal-values).
+///
+/// ```
+/// use integrators::{gsl, Integrator, Real};
+/// let mut qawc = gsl::QAWC::new(1000, 0.5)
+///                          .with_range(-1.0, 1.0);
+///
+/// // P \int_{-1}^{1} 1 / (x - 0.5) dx = log(1/3)
+/// let res = qawc.integrate(|_: Real| 1.0, 1e-8, 1e-12)
+///               .unwrap();
+/// assert!((res.value - (1f64 / 3.0).ln()).abs() < 1e-8);
+/// ```
+#[derive(Debug, Clone)]
+pub struct QAWC {
+    range_low: Real,
+    range_high: Real,
+    pole: Real,
+    wkspc: GSLIntegrationWorkspace,
+}
+
+impl QAWC {
+    /// Creates a new QAWC with enough memory for `nintervals` subintervals,
+    /// with a pole at `pole`. This will integrate over the range [0, 1]; to
+    /// change the integration bounds, see `with_range`.
+    pub fn new(nintervals: usize, pole: Real) -> Self {
+        QAWC {
+            range_low: 0.0,
+            range_high: 1.0,
+            pole,
+            wkspc: GSLIntegrationWorkspace::new(nintervals)
+        }
+    }
+
+    /// Discards the old workspace and allocates a new one with enough memory
+    /// for `nintervals` subintervals.
+    pub fn with_nintervals(self, nintervals: usize) -> Self {
+        QAWC {
+            wkspc: GSLIntegrationWorkspace::new(nintervals),
+            ..self
+        }
+    }
+
+    /// The subintervals of the most recent integration; see `Subinterval`.
+    pub fn subintervals(&self) -> Vec<Subinterval> {
+        self.wkspc.subintervals()
+    }
+
+    /// Use a different integration range. (Default = [0, 1])
+    pub fn with_range(self, range_low: Real, range_high: Real) -> Self {
+        QAWC { range_low, range_high, ..self }
+    }
+
+    /// Move the pole to `pole`.
+    pub fn with_pole(self, pole: Real) -> Self {
+        QAWC { pole, ..self }
+    }
+
+    pub fn pole(&self) -> Real {
+        self.pole
+    }
+}
+
+impl Integrator for QAWC {
+    type Success = IntegrationResult;
+    type Failure = GSLIntegrationError;
+    fn integrate<A, B, F: FnMut(A) -> B>(&mut self, fun: F, epsrel: Real, epsabs: Real) -> Result<Self::Success, Self::Failure>
+        where A: IntegrandInput,
+              B: IntegrandOutput
+    {
+        // GSL would return a generic `GSL_EINVAL` - catch it here instead
+        if self.pole == self.range_low || self.pole == self.range_high {
+            return Err(GSLIntegrationError::PoleAtEndpoint(self.pole))
+        }
+
+        let mut value: Real = 0.0;
+        let mut error: Real = 0.0;
+
+        let mut lp = LandingPad::new(fun);
+        let retcode = unsafe {
+            let mut gslfn = make_gsl_function(&mut lp, self.range_low, self.range_high)?;
+            bindings::gsl_integration_qawc(&mut gslfn.function,
+                                           self.range_low, self.range_high,
+                                           self.pole,
+                                           epsabs, epsrel,
+                                           self.wkspc.nintervals,
+                                           self.wkspc.wkspc,
+                                           &mut value,
+                                           &mut error)
+        };
+        lp.maybe_resume_unwind();
+
+        self.wkspc.integration_result(retcode, value, error)
+    }
+}
@@ -0,0 +1,137 @@
+use ::bindings;
+use ::{IntegrationResult, Integrator, Real};
+use ::ffi::LandingPad;
+use ::traits::{IntegrandInput, IntegrandOutput};
+
+use super::{make_gsl_function, GSLIntegrationError, GSLIntegrationWorkspace, Subinterval};
+use super::qawo::{QAWOTable, QAWOWeight};
+
+/// Quadrature Adaptive integration with Weight function for Fourier
+/// integrals. Integrates `f(x) sin(omega x)` or `f(x) cos(omega x)` over the
+/// semi-infinite interval `(a, +inf)`, by applying QAWO on successive cycles
+/// of the oscillation and extrapolating the series of results.
+///
+/// Note that QAWF only accepts an absolute error tolerance, so the `epsrel`
+/// argument to `integrate()` is ignored.
+///
+/// See GSL docs
+/// [here](https://www.gnu.org/software/gsl/doc/html/integration.html#qawf-adaptive-integration-for-fourier-integrals).
+///
+/// ```
+/// use integrators::{gsl, Integrator, Real};
+/// let mut qawf = gsl::QAWF::new(1000, gsl::QAWOWeight::Cosine, 1.0, 0.0);
+///
+/// // The Fourier cosine transform of exp(-x), at omega = 1
+/// let res = qawf.integrate(|x: Real| (-x).exp(), 0.0, 1e-10)
+///               .unwrap();
+/// assert!((res.value - 0.5).abs() < 1e-8);
+/// ```
+#[derive(Debug, Clone)]
+pub struct QAWF {
+    lower_bound: Real,
+    wkspc: GSLIntegrationWorkspace,
+    cycle_wkspc: GSLIntegrationWorkspace,
+    table: QAWOTable,
+}
+
+impl QAWF {
+    /// Creates a new QAWF with enough memory for `nintervals` subintervals,
+    /// to integrate the given `weight` function with frequency `omega` from
+    /// `lower_bound` to +infinity. The same number of subintervals is used
+    /// for each cycle of the oscillation, and the table of Chebyshev moments
+    /// has 50 levels; see `with_nlevels` to change that.
+    pub fn new(nintervals: usize, weight: QAWOWeight, omega: Real, lower_bound: Real) -> Self {
+        QAWF {
+            lower_bound,
+            wkspc: GSLIntegrationWorkspace::new(nintervals),
+            cycle_wkspc: GSLIntegrationWorkspace::new(nintervals),
+            // The length is overridden by GSL for each cycle
+            table: QAWOTable::new(omega, 1.0, weight, 50),
+        }
+    }
+
+    /// Discards the old workspaces and allocates new ones with enough memory
+    /// for `nintervals` subintervals.
+    pub fn with_nintervals(self, nintervals: usize) -> Self {
+        QAWF {
+            wkspc: GSLIntegrationWorkspace::new(nintervals),
+            cycle_wkspc: GSLIntegrationWorkspace::new(nintervals),
+            ..self
+        }
+    }
+
+    /// The cycles of the most recent integration, one per subinterval; see
+    /// `Subinterval`.
+    pub fn subintervals(&self) -> Vec<Subinterval> {
+        self.wkspc.subintervals()
+    }
+
+    /// Discards the old table of Chebyshev moments and allocates a new one
+    /// with `nlevels` levels of bisection. (Default = 50)
+    ///
+    /// # Panics
+    /// If `nlevels` is zero, panics.
+    pub fn with_nlevels(self, nlevels: usize) -> Self {
+        let table = QAWOTable::new(self.table.omega, self.table.length,
+                                   self.table.weight, nlevels);
+        QAWF { table, ..self }
+    }
+
+    /// Use a different lower bound of integration.
+    pub fn with_lower_bound(self, lower_bound: Real) -> Self {
+        QAWF { lower_bound, ..self }
+    }
+
+    /// Use a different frequency. This does not reallocate the table.
+    pub fn with_omega(mut self, omega: Real) -> Self {
+        let weight = self.table.weight;
+        self.table.set(omega, weight);
+        self
+    }
+
+    /// Use a different weight function. This does not reallocate the table.
+    pub fn with_weight(mut self, weight: QAWOWeight) -> Self {
+        let omega = self.table.omega;
+        self.table.set(omega, weight);
+        self
+    }
+
+    pub fn omega(&self) -> Real {
+        self.table.omega
+    }
+
+    pub fn weight(&self) -> QAWOWeight {
+        self.table.weight
+    }
+}
+
+impl Integrator for QAWF {
+    type Success = IntegrationResult;
+    type Failure = GSLIntegrationError;
+    fn integrate<A, B, F: FnMut(A) -> B>(&mut self, fun: F, _epsrel: Real, epsabs: Real) -> Result<Self::Success, Self::Failure>
+        where A: IntegrandInput,
+              B: IntegrandOutput
+    {
+        let mut value: Real = 0.0;
+        let mut error: Real = 0.0;
+
+        let mut lp = LandingPad::new(fun);
+        let retcode = unsafe {
+            let mut gslfn = make_gsl_function(&mut lp,
+                                              self.lower_bound,
+                                              self.lower_bound + 1.0)?;
+            bindings::gsl_integration_qawf(&mut gslfn.function,
+                                           self.lower_bound,
+                                           epsabs,
+                                           self.wkspc.nintervals,
+                                           self.wkspc.wkspc,
+                                           self.cycle_wkspc.wkspc,
+                                           self.table.table,
+                                           &mut value,
+                                           &mut error)
+        };
+        lp.maybe_resume_unwind();
+
+        self.wkspc.integration_result(retcode, value, error)
+    }
+}
@@ -0,0 +1,220 @@
+use std::fmt;
+
+use ::bindings;
+use ::{IntegrationResult, Integrator, Real};
+use ::ffi::LandingPad;
+use ::traits::{IntegrandInput, IntegrandOutput};
+
+use super::{make_gsl_function, GSLIntegrationError, GSLIntegrationWorkspace, Subinterval};
+
+/// The oscillatory weight function for QAWO integration.
+#[derive(Debug, Hash, Copy, Clone, PartialEq, Eq)]
+pub enum QAWOWeight {
+    /// Integrates `f(x) sin(omega x)`
+    Sine,
+    /// Integrates `f(x) cos(omega x)`
+    Cosine,
+}
+
+impl Into<bindings::gsl_integration_qawo_enum> for QAWOWeight {
+    fn into(self) -> bindings::gsl_integration_qawo_enum {
+        match self {
+            QAWOWeight::Sine => bindings::gsl_integration_qawo_enum_GSL_INTEG_SINE,
+            QAWOWeight::Cosine => bindings::gsl_integration_qawo_enum_GSL_INTEG_COSINE,
+        }
+    }
+}
+
+/// Owns a table of Chebyshev moments used by the QAWO and QAWF algorithms.
+pub(crate) struct QAWOTable {
+    pub(crate) omega: Real,
+    pub(crate) length: Real,
+    pub(crate) weight: QAWOWeight,
+    pub(crate) nlevels: usize,
+    pub(crate) table: *mut bindings::gsl_integration_qawo_table,
+}
+
+impl fmt::Debug for QAWOTable {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("QAWOTable")
+           .field("omega", &self.omega)
+           .field("length", &self.length)
+           .field("weight", &self.weight)
+           .field("nlevels", &self.nlevels)
+           .finish()
+    }
+}
+
+impl Clone for QAWOTable {
+    fn clone(&self) -> Self {
+        QAWOTable::new(self.omega, self.length, self.weight, self.nlevels)
+    }
+}
+
+impl QAWOTable {
+    pub(crate) fn new(omega: Real, length: Real, weight: QAWOWeight, nlevels: usize) -> Self {
+        assert!(nlevels > 0, "QAWO table must have at least one level");
+        QAWOTable {
+            // TODO: Check for null-pointer
+            table: unsafe {
+                bindings::gsl_integration_qawo_table_alloc(omega, length,
+                                                           weight.into(),
+                                                           nlevels)
+            },
+            omega, length, weight, nlevels
+        }
+    }
+
+    /// Changes the frequency and weight, without reallocating the table.
+    pub(crate) fn set(&mut self, omega: Real, weight: QAWOWeight) {
+        unsafe {
+            bindings::gsl_integration_qawo_table_set(self.table, omega,
+                                                     self.length,
+                                                     weight.into());
+        }
+        self.omega = omega;
+        self.weight = weight;
+    }
+
+    /// Changes the length of the integration interval, without reallocating
+    /// the table.
+    pub(crate) fn set_length(&mut self, length: Real) {
+        unsafe {
+            bindings::gsl_integration_qawo_table_set_length(self.table, length);
+        }
+        self.length = length;
+    }
+}
+
+impl Drop for QAWOTable {
+    fn drop(&mut self) {
+        unsafe {
+            bindings::gsl_integration_qawo_table_free(self.table)
+        }
+    }
+}
+
+/// Quadrature Adaptive integration with Weight function for Oscillatory
+/// integrands. Integrates `f(x) sin(omega x)` or `f(x) cos(omega x)` over a
+/// finite range, using the QAG algorithm with modified Clenshaw-Curtis rules
+/// on subintervals which contain many periods of the oscillation.
+///
+/// See GSL docs
+/// [here](https://www.gnu.org/software/gsl/doc/html/integration.html#qawo-adaptive-integration-for-oscillatory-functions).
+///
+/// ```
+/// use integrators::{gsl, Integrator, Real};
+/// let omega = 10.0;
+/// let mut qawo = gsl::QAWO::new(1000, gsl::QAWOWeight::Sine, omega);
+///
+/// let res = qawo.integrate(|x: Real| x, 1e-8, 1e-12)
+///               .unwrap();
+/// let exact = omega.sin() / omega.powi(2) - omega.cos() / omega;
+/// assert!((res.value - exact).abs() < 1e-8);
+/// ```
+#[derive(Debug, Clone)]
+pub struct QAWO {
+    range_low: Real,
+    range_high: Real,
+    wkspc: GSLIntegrationWorkspace,
+    table: QAWOTable,
+}
+
+impl QAWO {
+    /// Creates a new QAWO with enough memory for `nintervals` subintervals,
+    /// to integrate the given `weight` function with frequency `omega`. This
+    /// will integrate over the range [0, 1], with a 50-level table of
+    /// Chebyshev moments. To change the integration bounds, see `with_range`,
+    /// and to change the number of levels, see `with_nlevels`.
+    pub fn new(nintervals: usize, weight: QAWOWeight, omega: Real) -> Self {
+        QAWO {
+            range_low: 0.0,
+            range_high: 1.0,
+            wkspc: GSLIntegrationWorkspace::new(nintervals),
+            table: QAWOTable::new(omega, 1.0, weight, 50),
+        }
+    }
+
+    /// Discards the old workspace and allocates a new one with enough memory
+    /// for `nintervals` subintervals.
+    pub fn with_nintervals(self, nintervals: usize) -> Self {
+        QAWO {
+            wkspc: GSLIntegrationWorkspace::new(nintervals),
+            ..self
+        }
+    }
+
+    /// The subintervals of the most recent integration; see `Subinterval`.
+    pub fn subintervals(&self) -> Vec<Subinterval> {
+        self.wkspc.subintervals()
+    }
+
+    /// Discards the old table of Chebyshev moments and allocates a new one
+    /// with `nlevels` levels of bisection. QAWO will fail if it needs to
+    /// bisect an interval more than this many times. (Default = 50)
+    ///
+    /// # Panics
+    /// If `nlevels` is zero, panics.
+    pub fn with_nlevels(self, nlevels: usize) -> Self {
+        let table = QAWOTable::new(self.table.omega, self.table.length,
+                                   self.table.weight, nlevels);
+        QAWO { table, ..self }
+    }
+
+    /// Use a different integration range. This does not reallocate the
+    /// table. (Default = [0, 1])
+    pub fn with_range(mut self, range_low: Real, range_high: Real) -> Self {
+        self.table.set_length(range_high - range_low);
+        QAWO { range_low, range_high, ..self }
+    }
+
+    /// Use a different frequency. This does not reallocate the table.
+    pub fn with_omega(mut self, omega: Real) -> Self {
+        let weight = self.table.weight;
+        self.table.set(omega, weight);
+        self
+    }
+
+    /// Use a different weight function. This does not reallocate the table.
+    pub fn with_weight(mut self, weight: QAWOWeight) -> Self {
+        let omega = self.table.omega;
+        self.table.set(omega, weight);
+        self
+    }
+
+    pub fn omega(&self) -> Real {
+        self.table.omega
+    }
+
+    pub fn weight(&self) -> QAWOWeight {
+        self.table.weight
+    }
+}
+
+impl Integrator for QAWO {
+    type Success = IntegrationResult;
+    type Failure = GSLIntegrationError;
+    fn integrate<A, B, F: FnMut(A) -> B>(&mut self, fun: F, epsrel: Real, epsabs: Real) -> Result<Self::Success, Self::Failure>
+        where A: IntegrandInput,
+              B: IntegrandOutput
+    {
+        let mut value: Real = 0.0;
+        let mut error: Real = 0.0;
+
+        let mut lp = LandingPad::new(fun);
+        let retcode = unsafe {
+            let mut gslfn = make_gsl_function(&mut lp, self.range_low, self.range_high)?;
+            bindings::gsl_integration_qawo(&mut gslfn.function,
+                                           self.range_low,
+                                           epsabs, epsrel,
+                                           self.wkspc.nintervals,
+                                           self.wkspc.wkspc,
+                                           self.table.table,
+                                           &mut value,
+                                           &mut error)
+        };
+        lp.maybe_resume_unwind();
+
+        self.wkspc.integration_result(retcode, value, error)
+    }
+}
@@ -0,0 +1,186 @@
+use std::fmt;
+use std::os::raw::c_int;
+
+use ::bindings;
+use ::{IntegrationResult, Integrator, Real};
+use ::ffi::LandingPad;
+use ::traits::{IntegrandInput, IntegrandOutput};
+
+use super::{make_gsl_function, GSLIntegrationError, GSLIntegrationWorkspace, Subinterval};
+
+/// Parameters of the algebraic-logarithmic weight function used by QAWS,
+///
+/// `W(x) = (x - a)^alpha (b - x)^beta log^mu(x - a) log^nu(b - x)`
+///
+/// along with precomputed Chebyshev moments for it.
+pub struct QAWSTable {
+    alpha: Real,
+    beta: Real,
+    mu: i32,
+    nu: i32,
+    table: *mut bindings::gsl_integration_qaws_table,
+}
+
+impl fmt::Debug for QAWSTable {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("QAWSTable")
+           .field("alpha", &self.alpha)
+           .field("beta", &self.beta)
+           .field("mu", &self.mu)
+           .field("nu", &self.nu)
+           .finish()
+    }
+}
+
+impl Clone for QAWSTable {
+    fn clone(&self) -> Self {
+        QAWSTable::new(self.alpha, self.beta, self.mu, self.nu)
+            .expect("parameters were already validated")
+    }
+}
+
+impl QAWSTable {
+    /// Creates a new table for the weight function
+    /// `(x - a)^alpha (b - x)^beta log^mu(x - a) log^nu(b - x)`.
+    /// Returns `None` unless `alpha > -1`, `beta > -1`, and `mu` and `nu`
+    /// are each either 0 or 1.
+    pub fn new(alpha: Real, beta: Real, mu: i32, nu: i32) -> Option<Self> {
+        if !(alpha > -1.0) || !(beta > -1.0)
+                || (mu != 0 && mu != 1) || (nu != 0 && nu != 1) {
+            return None
+        }
+        Some(QAWSTable {
+            // TODO: Check for null-pointer
+            table: unsafe {
+                bindings::gsl_integration_qaws_table_alloc(alpha, beta,
+                                                           mu as c_int,
+                                                           nu as c_int)
+            },
+            alpha, beta, mu, nu
+        })
+    }
+
+    pub fn alpha(&self) -> Real {
+        self.alpha
+    }
+
+    pub fn beta(&self) -> Real {
+        self.beta
+    }
+
+    pub fn mu(&self) -> i32 {
+        self.mu
+    }
+
+    pub fn nu(&self) -> i32 {
+        self.nu
+    }
+}
+
+impl Drop for QAWSTable {
+    fn drop(&mut self) {
+        unsafe {
+            bindings::gsl_integration_qaws_table_free(self.table)
+        }
+    }
+}
+
+/// Quadrature Adaptive integration with Weight function for Singular
+/// integrands. Integrates `f(x) W(x)` over the range [a, b], where `W(x)`
+/// is an algebraic-logarithmic weight function with singularities at the
+/// endpoints, as described by a `QAWSTable`. Subintervals touching the
+/// endpoints are integrated with modified Clenshaw-Curtis rules, so that
+/// the singular behavior is handled exactly.
+///
+/// See GSL docs
+/// [here](https://www.gnu.org/software/gsl/doc/html/integration.html#qaws-adaptive-integration-for-singular-functions).
+///
+/// ```
+/// use integrators::{gsl, Integrator, Real};
+/// // W(x) = log(x)
+/// let table = gsl::QAWSTable::new(0.0, 0.0, 1, 0).unwrap();
+/// let mut qaws = gsl::QAWS::new(1000, table);
+///
+/// // \int_0^1 log(x) dx = -1
+/// let res = qaws.integrate(|_: Real| 1.0, 1e-8, 1e-12)
+///               .unwrap();
+/// assert!((res.value + 1.0).abs() < 1e-8);
+/// ```
+#[derive(Debug, Clone)]
+pub struct QAWS {
+    range_low: Real,
+    range_high: Real,
+    wkspc: GSLIntegrationWorkspace,
+    table: QAWSTable,
+}
+
+impl QAWS {
+    /// Creates a new QAWS with enough memory for `nintervals` subintervals,
+    /// and the weight function described by `table`. This will integrate
+    /// over the range [0, 1]; to change the integration bounds, see
+    /// `with_range`.
+    pub fn new(nintervals: usize, table: QAWSTable) -> Self {
+        QAWS {
+            range_low: 0.0,
+            range_high: 1.0,
+            wkspc: GSLIntegrationWorkspace::new(nintervals),
+            table
+        }
+    }
+
+    /// Discards the old workspace and allocates a new one with enough memory
+    /// for `nintervals` subintervals.
+    pub fn with_nintervals(self, nintervals: usize) -> Self {
+        QAWS {
+            wkspc: GSLIntegrationWorkspace::new(nintervals),
+            ..self
+        }
+    }
+
+    /// The subintervals of the most recent integration; see `Subinterval`.
+    pub fn subintervals(&self) -> Vec<Subinterval> {
+        self.wkspc.subintervals()
+    }
+
+    /// Use a different integration range. (Default = [0, 1])
+    pub fn with_range(self, range_low: Real, range_high: Real) -> Self {
+        QAWS { range_low, range_high, ..self }
+    }
+
+    /// Use a different weight function.
+    pub fn with_table(self, table: QAWSTable) -> Self {
+        QAWS { table, ..self }
+    }
+
+    pub fn table(&self) -> &QAWSTable {
+        &self.table
+    }
+}
+
+impl Integrator for QAWS {
+    type Success = IntegrationResult;
+    type Failure = GSLIntegrationError;
+    fn integrate<A, B, F: FnMut(A) -> B>(&mut self, fun: F, epsrel: Real, epsabs: Real) -> Result<Self::Success, Self::Failure>
+        where A: IntegrandInput,
+              B: IntegrandOutput
+    {
+        let mut value: Real = 0.0;
+        let mut error: Real = 0.0;
+
+        let mut lp = LandingPad::new(fun);
+        let retcode = unsafe {
+            let mut gslfn = make_gsl_function(&mut lp, self.range_low, self.range_high)?;
+            bindings::gsl_integration_qaws(&mut gslfn.function,
+                                           self.range_low, self.range_high,
+                                           self.table.table,
+                                           epsabs, epsrel,
+                                           self.wkspc.nintervals,
+                                           self.wkspc.wkspc,
+                                           &mut value,
+                                           &mut error)
+        };
+        lp.maybe_resume_unwind();
+
+        self.wkspc.integration_result(retcode, value, error)
+    }
+}
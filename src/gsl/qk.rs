@@ -0,0 +1,130 @@
+use ::bindings;
+use ::{IntegrationResult, IntegrationResultIter, Integrator, Real};
+use ::ffi::LandingPad;
+use ::traits::{IntegrandInput, IntegrandOutput, IntegrationResults};
+
+use super::{make_gsl_function, GSLErrorReason, GSLIntegrationError, GSLResult, QAGRule};
+
+/// The result of applying a single Gauss-Kronrod rule.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct GaussKronrodResult {
+    /// The result of the Kronrod rule.
+    pub value: Real,
+    /// An error estimate, from the difference between the Kronrod rule and
+    /// the embedded Gauss rule.
+    pub error: Real,
+    /// The result of the Kronrod rule applied to `|f(x)|`.
+    pub resabs: Real,
+    /// The result of the Kronrod rule applied to `|f(x) - mean(f)|`, where
+    /// `mean(f)` is the mean of the integrand over the interval.
+    pub resasc: Real,
+}
+
+impl IntegrationResults for GaussKronrodResult {
+    type Iterator = IntegrationResultIter;
+    fn results(self) -> Self::Iterator {
+        IntegrationResult {
+            value: self.value,
+            error: self.error,
+        }.results()
+    }
+}
+
+/// A single Gauss-Kronrod rule, applied once over the integration range.
+/// This is the building block of GSL's adaptive algorithms, and is useful
+/// for implementing custom adaptive schemes; see `apply`.
+///
+/// When used as an `Integrator`, integration fails if the error estimate of
+/// the rule does not meet the requested tolerances.
+///
+/// See GSL docs
+/// [here](https://www.gnu.org/software/gsl/doc/html/integration.html#c.gsl_integration_qk).
+///
+/// ```
+/// use integrators::{gsl, Real};
+/// let rule = gsl::GaussKronrodRule::new(gsl::QAGRule::Gauss21, 0.0, 1.0);
+///
+/// let res = rule.apply(|x: Real| x.exp()).unwrap();
+/// assert!((res.value - (1f64.exp() - 1.0)).abs() < 1e-12);
+/// assert!(res.resabs >= res.value.abs());
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct GaussKronrodRule {
+    range_low: Real,
+    range_high: Real,
+    rule: QAGRule,
+}
+
+impl GaussKronrodRule {
+    /// Creates a new Gauss-Kronrod rule which will integrate a 1-dimensional
+    /// function from `range_low` to `range_high`.
+    pub fn new(rule: QAGRule, range_low: Real, range_high: Real) -> Self {
+        GaussKronrodRule { range_low, range_high, rule }
+    }
+
+    /// Builder pattern to change the integration range.
+    pub fn with_range(self, range_low: Real, range_high: Real) -> Self {
+        GaussKronrodRule { range_low, range_high, ..self }
+    }
+
+    /// Use a different quadrature rule.
+    pub fn with_rule(self, rule: QAGRule) -> Self {
+        GaussKronrodRule { rule, ..self }
+    }
+
+    /// Applies the rule to `fun` over the integration range, and returns the
+    /// result regardless of its error estimate.
+    pub fn apply<A, B, F: FnMut(A) -> B>(&self, fun: F) -> GSLResult<GaussKronrodResult>
+        where A: IntegrandInput,
+              B: IntegrandOutput
+    {
+        let qk = match self.rule {
+            QAGRule::Gauss15 => bindings::gsl_integration_qk15,
+            QAGRule::Gauss21 => bindings::gsl_integration_qk21,
+            QAGRule::Gauss31 => bindings::gsl_integration_qk31,
+            QAGRule::Gauss41 => bindings::gsl_integration_qk41,
+            QAGRule::Gauss51 => bindings::gsl_integration_qk51,
+            QAGRule::Gauss61 => bindings::gsl_integration_qk61,
+        };
+
+        let mut value: Real = 0.0;
+        let mut error: Real = 0.0;
+        let mut resabs: Real = 0.0;
+        let mut resasc: Real = 0.0;
+
+        let mut lp = LandingPad::new(fun);
+        unsafe {
+            let mut gslfn = make_gsl_function(&mut lp, self.range_low, self.range_high)?;
+            qk(&mut gslfn.function,
+               self.range_low, self.range_high,
+               &mut value,
+               &mut error,
+               &mut resabs,
+               &mut resasc)
+        };
+        lp.maybe_resume_unwind();
+
+        Ok(GaussKronrodResult {
+            value, error, resabs, resasc
+        })
+    }
+}
+
+impl Integrator for GaussKronrodRule {
+    type Success = GaussKronrodResult;
+    type Failure = GSLIntegrationError;
+    fn integrate<A, B, F: FnMut(A) -> B>(&mut self, fun: F, epsrel: Real, epsabs: Real) -> Result<Self::Success, Self::Failure>
+        where A: IntegrandInput,
+              B: IntegrandOutput
+    {
+        let res = self.apply(fun)?;
+        if res.error > epsabs.max(epsrel * res.value.abs()) {
+            // The same failure QNG reports when no rule is precise enough
+            let reason = GSLErrorReason::internal("failed to reach tolerance with the Gauss-Kronrod rule",
+                                                  bindings::GSL_ETOL, file!(), line!());
+            Err(GSLIntegrationError::GSLError(bindings::GSL_ETOL.into(), Some(reason)))
+        } else {
+            Ok(res)
+        }
+    }
+}
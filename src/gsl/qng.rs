@@ -54,7 +54,7 @@ impl Integrator for QNG {
         lp.maybe_resume_unwind();
 
         if retcode != bindings::GSL_SUCCESS {
-            Err(GSLIntegrationError::GSLError(retcode.into()))
+            Err(GSLIntegrationError::from_retcode(retcode))
         } else {
             Ok(IntegrationResult {
                 value, error
@@ -0,0 +1,219 @@
+use std::fmt;
+use std::ffi::CStr;
+use std::os::raw::c_ulong;
+
+use ::bindings;
+use ::Real;
+
+/// The random number generation algorithms provided by GSL. See GSL docs
+/// [here](https://www.gnu.org/software/gsl/doc/html/rng.html#random-number-generator-algorithms)
+/// for a description of each.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RngType {
+    /// The Mersenne Twister of Matsumoto and Nishimura. This is GSL's default
+    /// generator.
+    MT19937,
+    /// RANLXD at luxury level 1, giving double-precision output.
+    Ranlxd1,
+    /// RANLXD at luxury level 2, giving double-precision output.
+    Ranlxd2,
+    /// RANLXS at luxury level 0, giving single-precision output.
+    Ranlxs0,
+    /// RANLXS at luxury level 1, giving single-precision output.
+    Ranlxs1,
+    /// RANLXS at luxury level 2, giving single-precision output.
+    Ranlxs2,
+    /// The original RANLUX generator of Lüscher, at the default luxury level.
+    Ranlux,
+    /// RANLUX at the highest luxury level.
+    Ranlux389,
+    /// A maximally equidistributed combined Tausworthe generator, with
+    /// L'Ecuyer's improved seeding.
+    Taus2,
+    /// A lagged-fibonacci generator with four taps.
+    Gfsr4,
+    /// L'Ecuyer's combined multiple recursive generator.
+    Cmrg,
+    /// L'Ecuyer's fifth-order multiple recursive generator.
+    Mrg,
+    /// The RANMAR lagged-fibonacci generator of Marsaglia, Zaman and Tsang.
+    Ranmar,
+}
+
+impl RngType {
+    fn gsl_type(&self) -> *const bindings::gsl_rng_type {
+        use self::RngType::*;
+        unsafe {
+            match *self {
+                MT19937 => bindings::gsl_rng_mt19937,
+                Ranlxd1 => bindings::gsl_rng_ranlxd1,
+                Ranlxd2 => bindings::gsl_rng_ranlxd2,
+                Ranlxs0 => bindings::gsl_rng_ranlxs0,
+                Ranlxs1 => bindings::gsl_rng_ranlxs1,
+                Ranlxs2 => bindings::gsl_rng_ranlxs2,
+                Ranlux => bindings::gsl_rng_ranlux,
+                Ranlux389 => bindings::gsl_rng_ranlux389,
+                Taus2 => bindings::gsl_rng_taus2,
+                Gfsr4 => bindings::gsl_rng_gfsr4,
+                Cmrg => bindings::gsl_rng_cmrg,
+                Mrg => bindings::gsl_rng_mrg,
+                Ranmar => bindings::gsl_rng_ranmar,
+            }
+        }
+    }
+}
+
+impl Default for RngType {
+    fn default() -> Self {
+        RngType::MT19937
+    }
+}
+
+/// A GSL random number generator, of a given `RngType` and seed. This can
+/// be given to GSL's Monte Carlo integrators, to control the sequence of
+/// points they sample, or used directly to generate random numbers.
+///
+/// Cloning an `Rng` copies its current state, so the clone produces the
+/// same sequence of numbers as the original from then on.
+///
+/// See GSL docs [here](https://www.gnu.org/software/gsl/doc/html/rng.html).
+///
+/// ```
+/// use integrators::gsl::{Rng, RngType};
+/// let mut rng = Rng::new(RngType::Taus2, 1234);
+/// let mut copy = rng.clone();
+///
+/// let x = rng.uniform();
+/// assert!(0.0 <= x && x < 1.0);
+/// assert_eq!(x, copy.uniform());
+///
+/// // Re-seeding restarts the sequence
+/// rng.set_seed(1234);
+/// assert_eq!(x, rng.uniform());
+/// assert_eq!((rng.rng_type(), rng.seed()), (RngType::Taus2, 1234));
+/// ```
+pub struct Rng {
+    rng_type: RngType,
+    seed: c_ulong,
+    rng: *mut bindings::gsl_rng,
+}
+
+impl fmt::Debug for Rng {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("Rng")
+           .field("rng_type", &self.rng_type)
+           .field("seed", &self.seed)
+           .finish()
+    }
+}
+
+impl Clone for Rng {
+    fn clone(&self) -> Self {
+        Rng {
+            rng_type: self.rng_type,
+            seed: self.seed,
+            // TODO: Check for null-pointer
+            rng: unsafe { bindings::gsl_rng_clone(self.rng) }
+        }
+    }
+}
+
+impl Default for Rng {
+    /// A Mersenne Twister, with GSL's default seed.
+    fn default() -> Self {
+        Rng::new(RngType::default(), 0)
+    }
+}
+
+impl Rng {
+    /// Creates a new generator of type `rng_type`, seeded with `seed`. A
+    /// seed of zero selects GSL's default seed for that generator.
+    pub fn new(rng_type: RngType, seed: c_ulong) -> Self {
+        let rng = Rng {
+            rng_type,
+            seed,
+            // TODO: Check for null-pointer
+            rng: unsafe { bindings::gsl_rng_alloc(rng_type.gsl_type()) }
+        };
+        unsafe { bindings::gsl_rng_set(rng.rng, seed) };
+        rng
+    }
+
+    /// Re-seeds the generator, restarting its sequence.
+    pub fn set_seed(&mut self, seed: c_ulong) {
+        self.seed = seed;
+        unsafe { bindings::gsl_rng_set(self.rng, seed) };
+    }
+
+    pub fn rng_type(&self) -> RngType {
+        self.rng_type
+    }
+
+    /// The seed the generator was last seeded with.
+    pub fn seed(&self) -> c_ulong {
+        self.seed
+    }
+
+    /// GSL's name for the generator algorithm.
+    pub fn name(&self) -> &'static str {
+        unsafe {
+            CStr::from_ptr::<'static>(bindings::gsl_rng_name(self.rng))
+        }.to_str().expect("gsl_rng_name should return valid static string")
+    }
+
+    /// The smallest value `get` can return.
+    pub fn min(&self) -> c_ulong {
+        unsafe { bindings::gsl_rng_min(self.rng) }
+    }
+
+    /// The largest value `get` can return.
+    pub fn max(&self) -> c_ulong {
+        unsafe { bindings::gsl_rng_max(self.rng) }
+    }
+
+    /// Returns a random integer, uniformly distributed between `min()` and
+    /// `max()` (inclusive).
+    pub fn get(&mut self) -> c_ulong {
+        unsafe { bindings::gsl_rng_get(self.rng) }
+    }
+
+    /// Returns a random number uniformly distributed in [0, 1).
+    pub fn uniform(&mut self) -> Real {
+        unsafe { bindings::gsl_rng_uniform(self.rng) }
+    }
+
+    /// Returns a random number uniformly distributed in (0, 1).
+    pub fn uniform_pos(&mut self) -> Real {
+        unsafe { bindings::gsl_rng_uniform_pos(self.rng) }
+    }
+
+    /// Returns a random integer uniformly distributed from 0 to `n - 1`.
+    ///
+    /// # Panics
+    /// If `n` is zero, or larger than the range of the generator, panics.
+    pub fn uniform_int(&mut self, n: c_ulong) -> c_ulong {
+        assert!(n > 0 && n - 1 <= self.max() - self.min(),
+                "n must be positive, and within the range of the generator");
+        unsafe { bindings::gsl_rng_uniform_int(self.rng, n) }
+    }
+
+    /// Returns a point sampled uniformly from the hyperrectangle with the
+    /// given `(lower, upper)` bounds in each dimension.
+    pub fn uniform_point(&mut self, range: &[(Real, Real)]) -> Vec<Real> {
+        range.iter()
+             .map(|&(low, high)| low + (high - low) * self.uniform())
+             .collect()
+    }
+
+    pub(crate) fn as_ptr(&self) -> *mut bindings::gsl_rng {
+        self.rng
+    }
+}
+
+impl Drop for Rng {
+    fn drop(&mut self) {
+        unsafe {
+            bindings::gsl_rng_free(self.rng)
+        }
+    }
+}
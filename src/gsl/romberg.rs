@@ -0,0 +1,191 @@
+use std::fmt;
+
+use ::bindings;
+use ::{IntegrationResult, IntegrationResultIter, Integrator, Real};
+use ::ffi::LandingPad;
+use ::traits::{IntegrandInput, IntegrandOutput, IntegrationResults};
+
+use super::{make_gsl_function, GSLIntegrationError};
+
+struct RombergWorkspace {
+    nsteps: usize,
+    wkspc: *mut bindings::gsl_integration_romberg_workspace
+}
+
+impl fmt::Debug for RombergWorkspace {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("RombergWorkspace")
+           .field("nsteps", &self.nsteps)
+           .finish()
+    }
+}
+
+impl Clone for RombergWorkspace {
+    fn clone(&self) -> Self {
+        RombergWorkspace::new(self.nsteps)
+    }
+}
+
+impl RombergWorkspace {
+    fn new(n: usize) -> Self {
+        assert!(n >= 1 && n <= 30, "Romberg workspace needs between 1 and 30 steps");
+        RombergWorkspace {
+            // TODO: Check for null-pointer
+            wkspc: unsafe {
+                bindings::gsl_integration_romberg_alloc(n)
+            },
+            nsteps: n
+        }
+    }
+
+    /// After a successful integration, which returned `value` using `neval`
+    /// evaluations, finds the difference between `value` and the previous
+    /// extrapolated estimate, from the two rows of the Richardson
+    /// extrapolation table left in the workspace. Returns `NaN` if they
+    /// can't be found.
+    fn last_difference(&self, value: Real, neval: usize) -> Real {
+        // Step `i` uses `2^i + 1` evaluations in total
+        if neval < 3 || !(neval - 1).is_power_of_two() {
+            return ::std::f64::NAN
+        }
+        let step = (neval - 1).trailing_zeros() as usize;
+        if step >= self.nsteps {
+            return ::std::f64::NAN
+        }
+
+        // GSL starts with the previous row in `work1`, and the current row
+        // in `work2`, and swaps them after every step
+        let wkspc = unsafe { &*self.wkspc };
+        let previous = if step % 2 == 1 { wkspc.work1 } else { wkspc.work2 };
+        (value - unsafe { *previous.offset(step as isize - 1) }).abs()
+    }
+}
+
+impl Drop for RombergWorkspace {
+    fn drop(&mut self) {
+        unsafe {
+            bindings::gsl_integration_romberg_free(self.wkspc)
+        }
+    }
+}
+
+/// The result of a `Romberg` integration, along with the number of integrand
+/// evaluations it used.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RombergResult {
+    pub value: Real,
+    pub error: Real,
+    /// The number of integrand evaluations used.
+    pub neval: usize,
+}
+
+impl IntegrationResults for RombergResult {
+    type Iterator = IntegrationResultIter;
+    fn results(self) -> Self::Iterator {
+        IntegrationResult {
+            value: self.value,
+            error: self.error,
+        }.results()
+    }
+}
+
+/// Romberg integration. Applies the trapezoidal rule with successively
+/// halved step sizes, and accelerates convergence with Richardson
+/// extrapolation. At step `n`, the integrand has been evaluated at
+/// `2^n + 1` points. This converges very quickly for smooth integrands,
+/// but poorly for anything else.
+///
+/// GSL does not report an error estimate for Romberg integration. The
+/// reported error is the difference between the last two extrapolated
+/// estimates, which is what GSL compares against the tolerance.
+///
+/// Requires GSL 2.5 or newer; with older versions of GSL, this integrator
+/// is left out of the crate. See GSL docs
+/// [here](https://www.gnu.org/software/gsl/doc/html/integration.html#romberg-integration).
+///
+/// ```
+/// use integrators::{gsl, Integrator, Real};
+/// let mut romberg = gsl::Romberg::new(20);
+///
+/// let res = romberg.integrate(|x: Real| x.exp(), 1e-10, 1e-12)
+///                  .unwrap();
+/// assert!((res.value - (1f64.exp() - 1.0)).abs() < 1e-10);
+/// assert!(res.neval <= (1 << 20) + 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Romberg {
+    range_low: Real,
+    range_high: Real,
+    wkspc: RombergWorkspace,
+}
+
+impl Romberg {
+    /// Creates a new Romberg integrator which takes at most `nsteps` steps
+    /// of Richardson extrapolation. This will integrate over the range
+    /// [0, 1]; to change the integration bounds, see `with_range`.
+    ///
+    /// # Panics
+    /// Unless `nsteps` is between 1 and 30 (inclusive), panics.
+    pub fn new(nsteps: usize) -> Self {
+        Romberg {
+            range_low: 0.0,
+            range_high: 1.0,
+            wkspc: RombergWorkspace::new(nsteps)
+        }
+    }
+
+    /// Discards the old workspace and allocates a new one which takes at most
+    /// `nsteps` steps.
+    ///
+    /// # Panics
+    /// Unless `nsteps` is between 1 and 30 (inclusive), panics.
+    pub fn with_nsteps(self, nsteps: usize) -> Self {
+        Romberg {
+            wkspc: RombergWorkspace::new(nsteps),
+            ..self
+        }
+    }
+
+    /// Use a different integration range. (Default = [0, 1])
+    pub fn with_range(self, range_low: Real, range_high: Real) -> Self {
+        Romberg { range_low, range_high, ..self }
+    }
+
+    pub fn nsteps(&self) -> usize {
+        self.wkspc.nsteps
+    }
+}
+
+impl Integrator for Romberg {
+    type Success = RombergResult;
+    type Failure = GSLIntegrationError;
+    fn integrate<A, B, F: FnMut(A) -> B>(&mut self, fun: F, epsrel: Real, epsabs: Real) -> Result<Self::Success, Self::Failure>
+        where A: IntegrandInput,
+              B: IntegrandOutput
+    {
+        let mut value: Real = 0.0;
+        let mut neval: usize = 0;
+
+        let mut lp = LandingPad::new(fun);
+        let retcode = unsafe {
+            let mut gslfn = make_gsl_function(&mut lp, self.range_low, self.range_high)?;
+            bindings::gsl_integration_romberg(&mut gslfn.function,
+                                              self.range_low, self.range_high,
+                                              epsabs, epsrel,
+                                              &mut value,
+                                              &mut neval,
+                                              self.wkspc.wkspc)
+        };
+        lp.maybe_resume_unwind();
+
+        if retcode != bindings::GSL_SUCCESS {
+            Err(GSLIntegrationError::from_retcode(retcode))
+        } else {
+            Ok(RombergResult {
+                value,
+                error: self.wkspc.last_difference(value, neval),
+                neval
+            })
+        }
+    }
+}
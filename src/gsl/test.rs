@@ -1,7 +1,14 @@
 //use std::intrinsics::unchecked_div;
 use ::Real;
 use ::Integrator;
-use super::{GSLIntegrationError, QNG, QAG, QAGS, QAGP};
+use ::{BatchFn, BatchIntegrator};
+use super::{GSLIntegrationError, GaussKronrodRule, QAGRule, QNG, QAG, QAGS, QAGP, QAWO, QAWOWeight, QAWF, QAWC,
+            QAWS, QAWSTable, CQUAD, GLFixed, MonteCarloPlain, Miser, GslVegas, Rng, RngType,
+            Subinterval, GSLErrorCode, GSLErrorReason, Componentwise};
+#[cfg(gsl_romberg)]
+use super::Romberg;
+#[cfg(gsl_fixed)]
+use super::{FixedQuadrature, FixedWeight};
 
 fn nan(_: Real) -> Real {
     ::std::f64::NAN
@@ -79,6 +86,23 @@ fn test_error_handling_nan() {
                format!("{}", res));
 }
 
+#[test]
+fn test_cquad_nan_handling() {
+    // 0/0 at x = 0, where the limit is 1
+    let sinc = |x: Real| x.sin() / x;
+    let mut cquad = CQUAD::new(100);
+    let res1 = cquad.integrate(sinc, 1e-10, 1e-12)
+                    .expect("integration should succeed");
+    assert!((res1.value - 0.946083070367183).abs() <= 1e-10);
+    assert!(res1.neval > 0);
+
+    // Integrable singularity at x = 0
+    let res2 = cquad.with_range(0.0, 4.0)
+                    .integrate(|x: Real| x.sqrt().recip(), 1e-8, 1e-12)
+                    .expect("integration should succeed");
+    assert!((res2.value - 4.0).abs() <= 1e-6);
+}
+
 #[test]
 fn test_error_handling_pos_inf() {
     let mut qng = QNG::new(0.0, 1.0);
@@ -124,3 +148,373 @@ fn test_qagp_singularity() {
                    .expect("integration should succeed");
     assert!((res2.value - 2f64).abs() <= res2.error);
 }
+
+#[test]
+fn test_qawo_oscillatory() {
+    let omega = 20.0;
+    let mut qawo = QAWO::new(1000, QAWOWeight::Cosine, omega)
+                        .with_range(0.0, 2.0);
+    // \int_0^2 x cos(omega x) dx
+    let exact_cos = (2.0 * omega).cos() / omega.powi(2)
+                    + 2.0 * (2.0 * omega).sin() / omega
+                    - 1.0 / omega.powi(2);
+    let res1 = qawo.integrate(|x: Real| x, 1e-8, 1e-12)
+                   .expect("integration should succeed");
+    assert!((res1.value - exact_cos).abs() <= 1e-8);
+
+    // \int_0^2 x sin(omega x) dx
+    let exact_sin = (2.0 * omega).sin() / omega.powi(2)
+                    - 2.0 * (2.0 * omega).cos() / omega;
+    let res2 = qawo.with_weight(QAWOWeight::Sine)
+                   .integrate(|x: Real| x, 1e-8, 1e-12)
+                   .expect("integration should succeed");
+    assert!((res2.value - exact_sin).abs() <= 1e-8);
+}
+
+#[test]
+fn test_qawf_fourier() {
+    let mut qawf = QAWF::new(1000, QAWOWeight::Sine, 2.0, 0.0);
+    // \int_0^\infty exp(-x) sin(2x) dx = 2 / (1 + 2^2)
+    let res1 = qawf.integrate(|x: Real| (-x).exp(), 0.0, 1e-10)
+                   .expect("integration should succeed");
+    assert!((res1.value - 0.4).abs() <= 1e-8);
+
+    // \int_1^\infty exp(-x) cos(2x) dx = exp(-1) (cos(2) - 2 sin(2)) / 5
+    let exact = (-1f64).exp() * (2f64.cos() - 2.0 * 2f64.sin()) / 5.0;
+    let res2 = qawf.with_weight(QAWOWeight::Cosine)
+                   .with_lower_bound(1.0)
+                   .integrate(|x: Real| (-x).exp(), 0.0, 1e-10)
+                   .expect("integration should succeed");
+    assert!((res2.value - exact).abs() <= 1e-8);
+}
+
+#[test]
+fn test_qawc_principal_value() {
+    let mut qawc = QAWC::new(1000, 0.25);
+    // P \int_0^1 x / (x - c) dx = 1 + c log((1 - c) / c)
+    let exact = 1.0 + 0.25 * 3f64.ln();
+    let res = qawc.integrate(|x: Real| x, 1e-8, 1e-12)
+                  .expect("integration should succeed");
+    assert!((res.value - exact).abs() <= 1e-8);
+
+    let mut qawc = qawc.with_pole(1.0);
+    assert_eq!(qawc.integrate(|x: Real| x, 1e-8, 1e-12),
+               Err(GSLIntegrationError::PoleAtEndpoint(1.0)));
+    let mut qawc = qawc.with_range(0.5, 2.0).with_pole(0.5);
+    assert_eq!(qawc.integrate(|x: Real| x, 1e-8, 1e-12),
+               Err(GSLIntegrationError::PoleAtEndpoint(0.5)));
+}
+
+#[test]
+fn test_qaws_endpoint_singularities() {
+    assert!(QAWSTable::new(-1.0, 0.0, 0, 0).is_none());
+    assert!(QAWSTable::new(0.0, -1.5, 0, 0).is_none());
+    assert!(QAWSTable::new(0.0, 0.0, 2, 0).is_none());
+    assert!(QAWSTable::new(0.0, 0.0, 0, -1).is_none());
+
+    // W(x) = 1 / sqrt(x - a)
+    let table = QAWSTable::new(-0.5, 0.0, 0, 0).unwrap();
+    let mut qaws = QAWS::new(1000, table).with_range(1.0, 2.0);
+    let res1 = qaws.integrate(|_: Real| 1.0, 1e-8, 1e-12)
+                   .expect("integration should succeed");
+    assert!((res1.value - 2.0).abs() <= 1e-8);
+
+    // W(x) = log(b - x) / sqrt(x - a)
+    let table = QAWSTable::new(-0.5, 0.0, 0, 1).unwrap();
+    let mut qaws = qaws.with_range(0.0, 1.0).with_table(table);
+    let res2 = qaws.integrate(|_: Real| 1.0, 1e-8, 1e-12)
+                   .expect("integration should succeed");
+    assert!((res2.value - (4.0 * 2f64.ln() - 4.0)).abs() <= 1e-8);
+}
+
+#[test]
+#[cfg(gsl_romberg)]
+fn test_romberg() {
+    let mut romberg = Romberg::new(20);
+    for &(low, high) in [(0.0, 1.0), (-10.0, 30.0)].iter() {
+        romberg = romberg.with_range(low, high);
+        let exp = quadratic_1_integral(low, high);
+        let res = romberg.integrate(quadratic_1, 1e-10, 1e-12)
+                         .expect("should converge");
+        assert!((res.value - exp).abs() <= 1e-10 * exp.abs());
+        assert!(res.error <= 1e-10 * res.value.abs());
+        assert!(res.neval <= (1 << 20) + 1);
+    }
+
+    // Too few steps to converge on a non-smooth integrand
+    let mut romberg = Romberg::new(3);
+    assert!(romberg.integrate(|x: Real| x.sqrt(), 1e-12, 1e-14).is_err());
+}
+
+#[test]
+#[cfg(gsl_fixed)]
+fn test_fixed_quadrature() {
+    assert!(FixedQuadrature::new(FixedWeight::Legendre, 0, 0.0, 1.0).is_none());
+    // Rejected by GSL, though not by the checks made before calling it
+    assert!(FixedQuadrature::new(FixedWeight::Legendre, 5, 0.0, 1e-300).is_none());
+    assert!(FixedQuadrature::new(FixedWeight::Legendre, 5, 1.0, 0.0).is_none());
+    assert!(FixedQuadrature::new(FixedWeight::Laguerre { alpha: -2.0 }, 5, 0.0, 1.0).is_none());
+    assert!(FixedQuadrature::new(FixedWeight::Hermite { alpha: 0.0 }, 5, 0.0, 0.0).is_none());
+
+    // Exact for polynomials of degree <= 2n - 1
+    let mut legendre = FixedQuadrature::new(FixedWeight::Legendre, 2, -10.0, 30.0).unwrap();
+    assert_eq!(legendre.nodes().len(), 2);
+    assert_eq!(legendre.weights().len(), 2);
+    assert!((legendre.weights().iter().sum::<Real>() - 40.0).abs() <= 1e-12);
+    let res1 = legendre.integrate(quadratic_1, 0.0, 0.0)
+                       .expect("integration should succeed");
+    let exp = quadratic_1_integral(-10.0, 30.0);
+    assert!((res1.value - exp).abs() <= 1e-10 * exp.abs());
+
+    // \int_0^\infty x^3 exp(-x) dx = 3!
+    let mut laguerre = FixedQuadrature::new(FixedWeight::Laguerre { alpha: 0.0 },
+                                            4, 0.0, 1.0).unwrap();
+    let res2 = laguerre.integrate(|x: Real| x.powi(3), 0.0, 0.0)
+                       .expect("integration should succeed");
+    assert!((res2.value - 6.0).abs() <= 1e-12);
+    let nodes = laguerre.nodes().to_vec();
+    assert_eq!(laguerre.clone().nodes(), &nodes[..]);
+}
+
+#[test]
+fn test_glfixed() {
+    let mut glfixed = GLFixed::new(2);
+    for &(low, high) in [(0.0, 1.0), (-10.0, 30.0)].iter() {
+        glfixed = glfixed.with_range(low, high);
+        let exp = quadratic_1_integral(low, high);
+        let res = glfixed.integrate(quadratic_1, 0.0, 0.0)
+                         .expect("integration should succeed");
+        assert!((res.value - exp).abs() <= 1e-10 * exp.abs());
+
+        // The nodes and weights reproduce the same rule
+        let sum = (0..glfixed.n()).map(|i| glfixed.point(i).unwrap())
+                                  .map(|(x, w)| w * quadratic_1(x))
+                                  .sum::<Real>();
+        assert!((sum - res.value).abs() <= 1e-10 * exp.abs());
+        assert_eq!(glfixed.point(2), None);
+    }
+
+    // Each node is evaluated exactly once
+    let mut calls = 0;
+    GLFixed::new(7).integrate(|x: Real| { calls += 1; x }, 0.0, 0.0)
+                   .expect("integration should succeed");
+    assert_eq!(calls, 7);
+}
+
+#[test]
+fn test_glfixed_batch() {
+    let mut glfixed = GLFixed::new(5).with_range(-1.0, 2.0);
+    let mut calls = 0;
+    let res = glfixed.integrate_batch(BatchFn::new(1, 2, |args: &[Real], output: &mut [Real]| {
+        calls += 1;
+        for (&x, out) in args.iter().zip(output.chunks_mut(2)) {
+            out[0] = quadratic_1(x);
+            out[1] = x.powi(4);
+        }
+    }), 0.0, 0.0).expect("integration should succeed");
+    assert_eq!(calls, 1);
+    assert_eq!(res.len(), 2);
+    assert!((res[0].value - quadratic_1_integral(-1.0, 2.0)).abs() <= 1e-10);
+    assert!((res[1].value - 33.0 / 5.0).abs() <= 1e-10);
+
+    let err = glfixed.integrate_batch(BatchFn::new(2, 1, |_: &[Real], _: &mut [Real]| ()), 0.0, 0.0);
+    assert_eq!(err, Err(GSLIntegrationError::InvalidInputDim(2)));
+}
+
+#[test]
+fn test_gauss_kronrod_rule() {
+    let mut rule = GaussKronrodRule::new(QAGRule::Gauss15, -10.0, 30.0);
+    let exp = quadratic_1_integral(-10.0, 30.0);
+    let res1 = rule.apply(quadratic_1)
+                   .expect("should evaluate");
+    assert!((res1.value - exp).abs() <= 1e-10 * exp.abs());
+    assert!(res1.resabs >= res1.value.abs());
+
+    // Usable wherever QNG is
+    let res2 = rule.integrate(quadratic_1, 1e-6, 1e-10)
+                   .expect("should converge");
+    assert_eq!(res1, res2);
+
+    // A single rule can't resolve a cusp
+    let mut rule = rule.with_rule(QAGRule::Gauss61);
+    let res3 = rule.integrate(|x: Real| x.abs().sqrt(), 1e-10, 1e-12)
+                   .expect_err("integration should fail");
+    assert_eq!("(GSL) error code 14, description: failed to reach the specified tolerance",
+               format!("{}", res3));
+    assert!(rule.apply(two_args).is_err());
+}
+
+#[test]
+fn test_monte_carlo() {
+    // \int_{[0,1]^2} x y dx dy = 1/4
+    let product = |(x, y): (Real, Real)| x * y;
+    let exp = 0.25;
+
+    let mut plain = MonteCarloPlain::new(10000).with_maxcalls(1000000);
+    let res1 = plain.integrate(product, 1e-3, 0.0)
+                    .expect("plain should converge");
+    assert!((res1.value - exp).abs() <= 5.0 * res1.error);
+    assert!(res1.neval >= 10000 && res1.neval <= 1000000);
+
+    let mut miser = Miser::new(10000).with_maxcalls(1000000);
+    let res2 = miser.integrate(product, 1e-3, 0.0)
+                    .expect("miser should converge");
+    assert!((res2.value - exp).abs() <= 5.0 * res2.error);
+
+    let mut vegas = GslVegas::new(10000);
+    assert_eq!(vegas.chisq(), None);
+    let res3 = vegas.integrate(product, 1e-3, 0.0)
+                    .expect("vegas should converge");
+    assert!((res3.value - exp).abs() <= 5.0 * res3.error);
+    assert!(vegas.chisq().is_some());
+
+    // A constant has no variance, so its chi-squared is meaningless; one
+    // batch is enough
+    let res4 = vegas.integrate(|(_, _): (Real, Real)| 2.0, 1e-6, 0.0)
+                    .expect("vegas should converge");
+    assert!((res4.value - 2.0).abs() <= 1e-12);
+    assert_eq!(res4.neval, 10000);
+
+    // Ranges must match the dimension of the integrand
+    let mut vegas = vegas.with_range(vec![(0.0, 2.0), (0.0, 2.0), (0.0, 2.0)]);
+    assert_eq!(vegas.integrate(product, 1e-3, 0.0).unwrap_err(),
+               GSLIntegrationError::InvalidInputDim(2));
+
+    // One batch is never enough for a tolerance this small, but its estimate
+    // is kept
+    let mut plain = plain.with_maxcalls(10000);
+    match plain.integrate(product, 1e-10, 0.0).unwrap_err() {
+        GSLIntegrationError::DidNotConverge(GSLErrorCode::MaxIter, partial, neval, reason) => {
+            assert!((partial.value - exp).abs() <= 5.0 * partial.error);
+            assert_eq!(neval, 10000);
+            assert!(reason.expect("should give a reason").file.ends_with("monte.rs"));
+        },
+        err => panic!("unexpected error: {}", err),
+    }
+}
+
+#[test]
+fn test_rng_reproducible() {
+    let product = |(x, y): (Real, Real)| x * y;
+    let integrate = |rng: Rng| {
+        Miser::new(10000).with_rng(rng)
+                         .integrate(product, 1e-2, 0.0)
+                         .expect("miser should converge")
+    };
+    let res1 = integrate(Rng::new(RngType::Ranlxd2, 42));
+    let res2 = integrate(Rng::new(RngType::Ranlxd2, 42));
+    let res3 = integrate(Rng::new(RngType::Ranlxd2, 43));
+    assert_eq!(res1, res2);
+    assert!(res1.value != res3.value);
+
+    let mut rng = Rng::new(RngType::MT19937, 7);
+    assert_eq!(rng.name(), "mt19937");
+    let point = rng.uniform_point(&[(0.0, 1.0), (-5.0, -4.0)]);
+    assert!(0.0 <= point[0] && point[0] < 1.0);
+    assert!(-5.0 <= point[1] && point[1] < -4.0);
+    assert!(rng.uniform_int(10) < 10);
+}
+
+#[test]
+fn test_subintervals() {
+    // Singular at x = 1, which is never the midpoint of a subinterval
+    let mut qags = QAGS::new(100).with_range(0.0, 3.0);
+    assert_eq!(qags.subintervals(), vec![]);
+
+    let res = qags.integrate(|x: Real| (x - 1.0).abs().sqrt().recip(), 1e-8, 1e-12)
+                  .expect("integration should succeed");
+    let subintervals = qags.subintervals();
+    assert!(subintervals.len() > 1);
+
+    // The subintervals tile the integration range
+    let mut sorted = subintervals.clone();
+    sorted.sort_by(|a, b| a.low.partial_cmp(&b.low).unwrap());
+    assert_eq!(sorted.first().unwrap().low, 0.0);
+    assert_eq!(sorted.last().unwrap().high, 3.0);
+    for (a, b) in sorted.iter().zip(sorted[1..].iter()) {
+        assert_eq!(a.high, b.low);
+    }
+
+    // The hardest subinterval touches the singularity
+    let &Subinterval { low, high, .. } = subintervals.iter()
+        .max_by(|a, b| a.error.partial_cmp(&b.error).unwrap())
+        .unwrap();
+    assert!(low <= 1.0 && 1.0 <= high);
+    let total = subintervals.iter().map(|s| s.value).sum::<Real>();
+    // QAGS extrapolates, so the sum is only roughly the integral
+    assert!((total - res.value).abs() <= 1e-2 * res.value);
+}
+
+#[test]
+fn test_partial_results() {
+    let mut qag = QAG::new(3).with_rule(QAGRule::Gauss15);
+    let res = qag.integrate(|x: Real| x.sqrt(), 1e-14, 1e-16)
+                 .expect_err("integration should run out of subintervals");
+    match res {
+        GSLIntegrationError::DidNotConverge(code, partial, nintervals, ref reason) => {
+            assert_eq!(code, GSLErrorCode::MaxIter);
+            assert_eq!(nintervals, 3);
+            let reason = reason.as_ref().expect("GSL should give a reason");
+            assert_eq!(reason.errno, GSLErrorCode::MaxIter.raw());
+            assert!(reason.file.ends_with("qag.c"));
+            assert!((partial.value - 2.0 / 3.0).abs() <= 1e-3);
+            assert!(partial.error > 0.0);
+        },
+        _ => panic!("expected partial results, got {:?}", res)
+    }
+}
+
+#[test]
+fn test_error_reason() {
+    use std::error::Error;
+    let mut qng = QNG::new(0.0, 1.0);
+    let res = qng.integrate(nan, 1e-6, 1e-10)
+                 .expect_err("integration should fail");
+    match res {
+        GSLIntegrationError::GSLError(code, Some(ref reason)) => {
+            assert_eq!(code, GSLErrorCode::Other(14));
+            assert_eq!(reason.errno, 14);
+            assert!(reason.file.ends_with("qng.c"));
+            assert!(reason.line > 0);
+            assert!(!reason.reason.is_empty());
+        },
+        _ => panic!("expected an error reason, got {:?}", res)
+    }
+    let source = res.source().expect("should have a source");
+    assert!(source.downcast_ref::<GSLErrorReason>().is_some());
+}
+
+#[test]
+fn test_componentwise() {
+    let mut neval = 0;
+    let mut glfixed = Componentwise::new(GLFixed::new(5).with_range(-10.0, 30.0));
+    let res = glfixed.integrate(|x: Real| {
+        neval += 1;
+        (quadratic_1(x), 2.0 * quadratic_1(x))
+    }, 0.0, 0.0).expect("integration should succeed");
+    let exp = quadratic_1_integral(-10.0, 30.0);
+    assert_eq!(res.len(), 2);
+    assert!((res[0].value - exp).abs() <= 1e-10 * exp.abs());
+    assert!((res[1].value - 2.0 * exp).abs() <= 1e-10 * exp.abs());
+    // Every node is shared between components; plus the probe at the center
+    assert!(neval <= 6);
+
+    // Without a cache, each component evaluates the integrand again
+    let mut neval = 0;
+    let mut glfixed = glfixed.with_cache_size(0);
+    let res2 = glfixed.integrate(|x: Real| {
+        neval += 1;
+        (quadratic_1(x), 2.0 * quadratic_1(x))
+    }, 0.0, 0.0).expect("integration should succeed");
+    assert_eq!(res, res2);
+    assert!(neval >= 10);
+
+    let mut qags = Componentwise::new(QAGS::new(100));
+    let res = qags.integrate(two_outputs, 1e-6, 1e-10)
+                  .expect("integration should succeed");
+    assert_eq!(res.len(), 2);
+    assert_eq!(qags.integrate(two_args, 1e-6, 1e-10),
+               Err(GSLIntegrationError::InvalidInputDim(2)));
+    assert_eq!(qags.integrate(|_: Real| vec![], 1e-6, 1e-10),
+               Err(GSLIntegrationError::InvalidOutputDim(0)));
+}
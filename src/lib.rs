@@ -1,5 +1,7 @@
 pub mod traits;
 pub mod ffi;
+mod nested;
+mod transform;
 
 #[cfg(any(feature = "cuba", feature = "gsl"))]
 mod bindings;
@@ -22,8 +24,13 @@ pub type Real6 = (Real, Real, Real, Real, Real, Real);
 pub type Real7 = (Real, Real, Real, Real, Real, Real, Real);
 pub type Real8 = (Real, Real, Real, Real, Real, Real, Real, Real);
 
+use std::{iter, vec};
+
 pub use traits::{Integrator, IntegrandInput, IntegrandOutput,
-                 IntegrationResults};
+                 IntegrationResults, BatchFn, BatchIntegrand, BatchIntegrator};
+pub use nested::{Nested, NestedError};
+pub use transform::{Logarithmic, Power, Sidi, TanhSinh, Transform, Transformed,
+                    TransformedError};
 
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub struct IntegrationResult {
@@ -58,3 +65,12 @@ impl self::traits::IntegrationResults for IntegrationResult {
         }
     }
 }
+
+/// Integrators which compute several integrals separately can return a `Vec`
+/// of results, one for each.
+impl<R: IntegrationResults> self::traits::IntegrationResults for Vec<R> {
+    type Iterator = iter::FlatMap<vec::IntoIter<R>, R::Iterator, fn(R) -> R::Iterator>;
+    fn results(self) -> Self::Iterator {
+        self.into_iter().flat_map(R::results as fn(R) -> R::Iterator)
+    }
+}
@@ -0,0 +1,178 @@
+use std::{error, fmt, marker};
+
+use super::{IntegrationResult, Real};
+use super::traits::{ArgCount, Integrator, IntegrandInput, IntegrandOutput, IntegrationResults,
+                    RawArgs};
+
+/// All the arguments of an integrand `A` after the first, so that the
+/// integral over them can be handed to another integrator.
+struct Tail<A>(marker::PhantomData<A>);
+
+impl<A: IntegrandInput> ArgCount for Tail<A> {
+    fn count() -> usize {
+        A::input_size().saturating_sub(1)
+    }
+}
+
+/// Iterated integration, for integrating over several dimensions with
+/// one-dimensional integrators. The `outer` integrator integrates over the
+/// first argument of the integrand; for each value of the first argument,
+/// the `inner` integrator integrates over the rest.
+///
+/// For two-dimensional integrands, `inner` should be a one-dimensional
+/// integrator. For more dimensions, `inner` can itself be a `Nested`
+/// integrator, e.g. `Nested::new(a, Nested::new(b, c))` for three.
+///
+/// The inner integrals are computed to tolerances tighter than the requested
+/// ones by the factor `inner_tolerance` (see `with_inner_tolerance`), so
+/// that their errors don't spoil the outer integral. The reported error of
+/// each result is the error of the outer integral, plus the largest relative
+/// error of any of the inner integrals times the magnitude of the result.
+///
+/// ```
+/// # #[cfg(feature = "gsl")]
+/// # fn main() {
+/// use integrators::{gsl, Integrator, Nested, Real};
+/// let mut nested = Nested::new(gsl::QAG::new(100).with_range(0.0, 2.0),
+///                              gsl::QAGS::new(100).with_range(0.0, 3.0));
+///
+/// let res = nested.integrate(|(x, y): (Real, Real)| x * y, 1e-8, 1e-10)
+///                 .unwrap();
+/// assert!((res[0].value - 9.0).abs() < 1e-8);
+/// # }
+/// # #[cfg(not(feature = "gsl"))]
+/// # fn main() {}
+/// ```
+#[derive(Debug, Clone)]
+pub struct Nested<O, I> {
+    outer: O,
+    inner: I,
+    inner_tolerance: Real,
+}
+
+impl<O, I> Nested<O, I> {
+    /// Combines the `outer` integrator, for the first argument of the
+    /// integrand, and the `inner` integrator, for the rest.
+    pub fn new(outer: O, inner: I) -> Self {
+        Nested {
+            outer,
+            inner,
+            inner_tolerance: 0.1
+        }
+    }
+
+    /// Integrate the inner integrals to `inner_tolerance` times the requested
+    /// tolerances. (Default = 0.1)
+    pub fn with_inner_tolerance(self, inner_tolerance: Real) -> Self {
+        Nested { inner_tolerance, ..self }
+    }
+
+    pub fn outer(&self) -> &O {
+        &self.outer
+    }
+
+    pub fn outer_mut(&mut self) -> &mut O {
+        &mut self.outer
+    }
+
+    pub fn inner(&self) -> &I {
+        &self.inner
+    }
+
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.inner
+    }
+}
+
+impl<O, I> Integrator for Nested<O, I>
+    where O: Integrator,
+          I: Integrator
+{
+    type Success = Vec<IntegrationResult>;
+    type Failure = NestedError<O::Failure, I::Failure>;
+    fn integrate<A, B, F: FnMut(A) -> B>(&mut self, mut fun: F, epsrel: Real, epsabs: Real) -> Result<Self::Success, Self::Failure>
+        where A: IntegrandInput,
+              B: IntegrandOutput
+    {
+        let Nested { ref mut outer, ref mut inner, inner_tolerance } = *self;
+
+        let mut args = vec![0.0; A::input_size()];
+        let mut ncomp: usize = 1;
+        let mut max_inner_relerr: Real = 0.0;
+        let mut inner_failure: Option<I::Failure> = None;
+
+        let res = {
+            let inner_failure = &mut inner_failure;
+            outer.integrate(|x: Real| {
+                // Once an inner integral fails, the result is an error anyway
+                if inner_failure.is_some() {
+                    return vec![0.0; ncomp]
+                }
+
+                let res = inner.integrate(|tail: RawArgs<Tail<A>>| {
+                    args[0] = x;
+                    args[1..].copy_from_slice(&tail.args[..]);
+                    fun(A::from_args(&args[..]))
+                }, epsrel * inner_tolerance, epsabs * inner_tolerance);
+
+                match res {
+                    Ok(res) => {
+                        let values = res.results().map(|r| {
+                            if r.value != 0.0 {
+                                max_inner_relerr = max_inner_relerr.max(r.error / r.value.abs());
+                            }
+                            r.value
+                        }).collect::<Vec<Real>>();
+                        ncomp = values.len();
+                        values
+                    },
+                    Err(e) => {
+                        *inner_failure = Some(e);
+                        vec![0.0; ncomp]
+                    }
+                }
+            }, epsrel, epsabs)
+        };
+
+        if let Some(e) = inner_failure {
+            return Err(NestedError::Inner(e))
+        }
+        let res = res.map_err(NestedError::Outer)?;
+
+        Ok(res.results().map(|IntegrationResult { value, error }| {
+            IntegrationResult {
+                value,
+                error: error + max_inner_relerr * value.abs()
+            }
+        }).collect())
+    }
+}
+
+/// The failure of a `Nested` integrator: either the outer integral, or one
+/// of the inner integrals, failed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NestedError<O, I> {
+    Outer(O),
+    Inner(I),
+}
+
+impl<O: fmt::Display, I: fmt::Display> fmt::Display for NestedError<O, I> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            &NestedError::Outer(ref e) => write!(fmt, "outer integral failed: {}", e),
+            &NestedError::Inner(ref e) => write!(fmt, "inner integral failed: {}", e),
+        }
+    }
+}
+
+impl<O, I> error::Error for NestedError<O, I>
+    where O: error::Error + 'static,
+          I: error::Error + 'static
+{
+    fn source(&self) -> Option<&(error::Error + 'static)> {
+        match self {
+            &NestedError::Outer(ref e) => Some(e),
+            &NestedError::Inner(ref e) => Some(e),
+        }
+    }
+}
@@ -1,6 +1,13 @@
-use super::{Integrator, Real};
+use super::{Integrator, Logarithmic, Nested, NestedError, Power, Real};
 #[cfg(feature = "cuba")]
-use super::cuba::{Cuhre, CubaError, Vegas};
+use super::{BatchFn, BatchIntegrator};
+#[cfg(feature = "cuba")]
+use super::cuba::{Cuhre, CubaError, CubaFlags, CubaIntegrationResults, Divonne, Hyperrectangle,
+                  IntegrationRange, RandomNumberSource, Suave, Vegas};
+#[cfg(feature = "cuba")]
+use super::{Sidi, TanhSinh, Transform, Transformed, TransformedError};
+#[cfg(feature = "gsl")]
+use super::gsl::{GSLIntegrationError, QAG, QAGS, GLFixed};
 
 #[test]
 #[cfg(feature = "cuba")]
@@ -16,3 +23,419 @@ fn test_simple_integration() {
                             1e-4, 1e-12);
     assert!(b.is_ok());
 }
+
+#[test]
+#[cfg(feature = "cuba")]
+fn test_divonne_peak() {
+    // A narrow 2D Gaussian, centered in the unit square
+    let sigma: Real = 0.01;
+    let gaussian = |(x, y): (Real, Real)| {
+        (-((x - 0.5).powi(2) + (y - 0.5).powi(2)) / (2.0 * sigma * sigma)).exp()
+    };
+    let exact = 2.0 * ::std::f64::consts::PI * sigma * sigma;
+
+    let mut divonne = Divonne::new().with_maxeval(1000000);
+    let a = divonne.integrate(|a: Real| a, 1e-4, 1e-12);
+    assert_eq!(a, Err(CubaError::BadDim("divonne", 1)));
+
+    let mut divonne = divonne.with_xgiven(vec![vec![0.5, 0.5, 0.5]]);
+    let c = divonne.integrate(gaussian, 1e-4, 1e-12);
+    assert_eq!(c, Err(CubaError::BadXGiven(3, 2)));
+
+    let mut divonne = divonne.with_xgiven(vec![vec![0.5, 0.5]]);
+    let b = divonne.integrate(gaussian, 1e-4, 1e-12)
+                   .expect("should converge");
+    assert!((b.results[0].value - exact).abs() < 1e-2 * exact);
+
+    let mut divonne = Divonne::new()
+                              .with_maxeval(1000000)
+                              .with_peakfinder(1, |bounds: &[(Real, Real)]| {
+                                  if bounds.iter().all(|&(lo, hi)| lo <= 0.5 && 0.5 <= hi) {
+                                      vec![vec![0.5, 0.5]]
+                                  } else {
+                                      vec![]
+                                  }
+                              });
+    let c = divonne.integrate(gaussian, 1e-4, 1e-12)
+                   .expect("should converge");
+    assert!((c.results[0].value - exact).abs() < 1e-2 * exact);
+}
+
+#[test]
+#[cfg(feature = "gsl")]
+fn test_nested() {
+    // \int_0^1 \int_0^2 \int_0^3 x y^2 z^3 dz dy dx = (1/2) (8/3) (81/4)
+    let mut nested = Nested::new(QAG::new(100),
+                                 Nested::new(QAGS::new(100).with_range(0.0, 2.0),
+                                             GLFixed::new(4).with_range(0.0, 3.0)));
+    let exact = 0.5 * (8.0 / 3.0) * (81.0 / 4.0);
+    let res = nested.integrate(|(x, y, z): (Real, Real, Real)| x * y.powi(2) * z.powi(3),
+                               1e-8, 1e-12)
+                    .expect("nested integration should converge");
+    assert_eq!(res.len(), 1);
+    assert!((res[0].value - exact).abs() <= 1e-8 * exact);
+    assert!(res[0].error <= 1e-8 * exact);
+
+    // Failures of inner integrals are reported as such
+    let mut nested = Nested::new(QAG::new(100), QAG::new(100));
+    let err = nested.integrate(|(_, y): (Real, Real)| (y - 0.5).powi(-2), 1e-6, 1e-10)
+                    .expect_err("inner integral should fail");
+    match err {
+        NestedError::Inner(GSLIntegrationError::GSLError(..)) => {},
+        _ => panic!("expected inner failure, got {:?}", err)
+    }
+    assert!(nested.integrate(|x: Real| x, 1e-6, 1e-10).is_err());
+}
+
+#[test]
+#[cfg(feature = "cuba")]
+fn test_cuba_domain() {
+    // \int_{-1}^1 \int_2^5 x^2 y dy dx = (2/3) (21/2)
+    let domain = Hyperrectangle::new(vec![(-1.0, 1.0), (2.0, 5.0)]);
+    let exact = 7.0;
+    let fun = |(x, y): (Real, Real)| {
+        assert!(-1.0 <= x && x <= 1.0 && 2.0 <= y && y <= 5.0);
+        x * x * y
+    };
+
+    let mut cuhre = Cuhre::new(100000).with_domain(domain.clone());
+    let res = cuhre.integrate(fun, 1e-8, 1e-12)
+                   .expect("cuhre should converge");
+    assert!((res.results[0].value - exact).abs() <= 1e-6);
+
+    let mut divonne = Divonne::new().with_maxeval(1000000)
+                                    .with_domain(domain.clone())
+                                    .with_xgiven(vec![vec![0.0, 3.0]]);
+    let res = divonne.integrate(fun, 1e-4, 1e-12)
+                     .expect("divonne should converge");
+    assert!((res.results[0].value - exact).abs() <= 1e-2);
+
+    // The domain must match the dimension of the integrand
+    let mut cuhre = cuhre.with_domain(Hyperrectangle::new(vec![(0.0, 1.0); 3]));
+    assert_eq!(cuhre.integrate(fun, 1e-8, 1e-12),
+               Err(CubaError::BadDim("cuhre", 2)));
+}
+
+#[test]
+#[cfg(feature = "cuba")]
+fn test_infinite_ranges() {
+    use std::f64::INFINITY;
+    let ranges = [IntegrationRange::new(2.0, INFINITY),
+                  IntegrationRange::new(-INFINITY, -3.0),
+                  IntegrationRange::new(-INFINITY, INFINITY)];
+    for range in ranges.iter() {
+        assert!(!range.is_finite());
+        for &x in [0.1, 0.5, 0.9].iter() {
+            let y = range.transform(x);
+            assert!((range.inverse_transform(y) - x).abs() < 1e-12);
+            // Compare to a central difference
+            let h = 1e-6;
+            let numeric = (range.transform(x + h) - range.transform(x - h)) / (2.0 * h);
+            assert!((range.jacobian_at(x) - numeric).abs() < 1e-6 * numeric.abs());
+        }
+    }
+    assert_eq!(ranges[0].transform(0.0), 2.0);
+    assert_eq!(ranges[1].transform(1.0), -3.0);
+    assert_eq!(ranges[0].jacobian(), INFINITY);
+    assert_eq!(Hyperrectangle::new(vec![(0.0, 2.0), (0.0, INFINITY)]).jacobian(), INFINITY);
+    assert_eq!(Hyperrectangle::new(vec![(1.0, 1.0), (0.0, INFINITY)]).jacobian(), 0.0);
+
+    // Points at infinity in a zero-length domain contribute nothing, too
+    let res = Cuhre::new(100000).with_domain(Hyperrectangle::new(vec![(1.0, 1.0), (0.0, INFINITY)]))
+                    .integrate(|(_, y): (Real, Real)| (-y).exp(), 1e-6, 1e-12)
+                    .expect("cuhre should converge");
+    assert_eq!(res.results[0].value, 0.0);
+
+    // \int_1^\infty \int_{-\infty}^0 \int_{-\infty}^\infty exp(-x + y - z^2) dz dy dx
+    //     = exp(-1) sqrt(pi)
+    let domain = Hyperrectangle::new(vec![(1.0, INFINITY), (-INFINITY, 0.0),
+                                          (-INFINITY, INFINITY)]);
+    let exact = (-1f64).exp() * ::std::f64::consts::PI.sqrt();
+    let res = Cuhre::new(1000000).with_domain(domain)
+                    .integrate(|(x, y, z): (Real, Real, Real)| (-x + y - z * z).exp(),
+                               1e-6, 1e-12)
+                    .expect("cuhre should converge");
+    assert!((res.results[0].value - exact).abs() <= 1e-5 * exact);
+}
+
+#[test]
+#[cfg(feature = "cuba")]
+fn test_transforms() {
+    let transforms: Vec<Box<Transform>> = vec![Box::new(Logarithmic::new(3.0)),
+                                               Box::new(Power::new(2.0)),
+                                               Box::new(TanhSinh::new(3.0)),
+                                               Box::new(Sidi::new(2)),
+                                               Box::new(Sidi::new(3))];
+    for transform in transforms.iter() {
+        assert_eq!(transform.transform(0.0), 0.0);
+        assert_eq!(transform.transform(1.0), 1.0);
+        for &t in [0.3, 0.5, 0.7].iter() {
+            // Compare to a central difference
+            let h = 1e-6;
+            let numeric = (transform.transform(t + h) - transform.transform(t - h)) / (2.0 * h);
+            assert!((transform.jacobian(t) - numeric).abs() < 1e-6 * numeric.abs());
+        }
+    }
+
+    // \int_0^1 \int_0^1 1 / sqrt(x y) dx dy = 4, singular along both axes
+    let fun = |(x, y): (Real, Real)| (x * y).sqrt().recip();
+    let mut power = Transformed::new(Cuhre::new(1000000), Power::new(2.0));
+    let res = power.integrate(fun, 1e-8, 1e-12).expect("cuhre should converge");
+    assert!((res.results[0].value - 4.0).abs() <= 1e-7);
+
+    // No part of the integral is lost near the endpoints
+    let mut tanh_sinh = Transformed::new(Cuhre::new(1000000), TanhSinh::new(4.0));
+    let res = tanh_sinh.integrate(fun, 1e-9, 1e-12).expect("cuhre should converge");
+    assert!((res.results[0].value - 4.0).abs() <= 1e-8);
+
+    let mixed: Vec<Box<Transform>> = vec![Box::new(TanhSinh::new(3.0)),
+                                          Box::new(Sidi::new(2))];
+    let mut mixed = Transformed::per_dimension(Cuhre::new(1000000), mixed);
+    let res = mixed.integrate(fun, 1e-6, 1e-12).expect("cuhre should converge");
+    assert!((res.results[0].value - 4.0).abs() <= 1e-5);
+
+    // The number of transforms is checked before integrating
+    let mut short = Transformed::per_dimension(Cuhre::new(1000000), vec![Power::new(2.0)]);
+    assert_eq!(short.integrate(fun, 1e-6, 1e-12).err(), Some(TransformedError::BadDim(1, 2)));
+
+    // Cuhre's first point is the center, where this Jacobian vanishes; the
+    // zero output must still have as many components as the integrand
+    struct UpperHalf;
+    impl Transform for UpperHalf {
+        fn transform(&self, t: Real) -> Real {
+            (2.0 * t - 1.0).max(0.0)
+        }
+
+        fn jacobian(&self, t: Real) -> Real {
+            if t <= 0.5 { 0.0 } else { 2.0 }
+        }
+    }
+    let mut upper = Transformed::new(Cuhre::new(1000000), UpperHalf);
+    let res = upper.integrate(|(x, _): (Real, Real)| vec![1.0, x], 1e-8, 1e-12)
+                   .expect("cuhre should converge");
+    assert!((res.results[0].value - 1.0).abs() <= 1e-7);
+    assert!((res.results[1].value - 0.5).abs() <= 1e-7);
+}
+
+#[test]
+#[should_panic]
+fn test_power_transform_rejects_nonpositive_p() {
+    Power::new(0.0);
+}
+
+#[test]
+#[should_panic]
+fn test_logarithmic_transform_rejects_zero_k() {
+    Logarithmic::new(0.0);
+}
+
+#[test]
+#[cfg(feature = "cuba")]
+fn test_parallel_integration() {
+    use std::panic;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    // \int_0^1 \int_0^1 \int_0^1 exp(-(x + y + z)) dx dy dz = (1 - 1/e)^3
+    let exact = (1.0 - (-1f64).exp()).powi(3);
+    let calls = AtomicUsize::new(0);
+    let fun = |(x, y, z): (Real, Real, Real)| {
+        calls.fetch_add(1, Ordering::Relaxed);
+        (-(x + y + z)).exp()
+    };
+
+    // Batching doesn't change which points Cuhre samples
+    let serial = Cuhre::new(1000000).integrate(&fun, 1e-8, 1e-12)
+                                    .expect("cuhre should converge");
+    let mut cuhre = Cuhre::new(1000000).with_nvec(100).with_threads(4);
+    let parallel = cuhre.integrate_parallel(&fun, 1e-8, 1e-12)
+                        .expect("cuhre should converge");
+    assert_eq!(serial.neval, parallel.neval);
+    assert!((serial.results[0].value - parallel.results[0].value).abs() <= 1e-14);
+    assert!((parallel.results[0].value - exact).abs() <= 1e-8 * exact);
+
+    let domain = Hyperrectangle::new(vec![(0.0, 1.0), (0.0, 2.0), (0.0, 3.0)]);
+    let mut vegas = Vegas::default().with_maxeval(1000000).with_domain(domain);
+    let res = vegas.integrate_parallel(|(x, y, z): (Real, Real, Real)| x * y * z, 1e-3, 1e-12)
+                   .expect("vegas should converge");
+    assert!((res.results[0].value - 4.5).abs() <= 1e-2 * 4.5);
+
+    // A panic on any of the threads is propagated to the caller
+    calls.store(0, Ordering::Relaxed);
+    let res = panic::catch_unwind(|| {
+        Cuhre::new(1000000).with_threads(4).integrate_parallel(|x: (Real, Real)| {
+            if calls.fetch_add(1, Ordering::Relaxed) > 1000 {
+                panic!("integrand failed")
+            }
+            x.0 * x.1
+        }, 1e-12, 1e-14)
+    });
+    assert!(res.is_err());
+}
+
+#[test]
+#[cfg(feature = "cuba")]
+fn test_batch_integration() {
+    use std::panic;
+
+    // \int_0^1 \int_0^2 (x^2 y, x + y) dy dx = (2/3, 3)
+    let domain = Hyperrectangle::new(vec![(0.0, 1.0), (0.0, 2.0)]);
+    let mut batches = 0;
+    let mut largest = 0;
+    let res = {
+        let batched = BatchFn::new(2, 2, |args: &[Real], output: &mut [Real]| {
+            batches += 1;
+            largest = largest.max(args.len() / 2);
+            for (x, out) in args.chunks(2).zip(output.chunks_mut(2)) {
+                out[0] = x[0] * x[0] * x[1];
+                out[1] = x[0] + x[1];
+            }
+        });
+        Cuhre::new(1000000).with_domain(domain.clone()).with_nvec(64)
+                           .integrate_batch(batched, 1e-10, 1e-12)
+                           .expect("cuhre should converge")
+    };
+    assert!(batches > 0 && largest > 1 && largest <= 64);
+    assert!((res.results[0].value - 2.0 / 3.0).abs() <= 1e-10);
+    assert!((res.results[1].value - 3.0).abs() <= 1e-10);
+
+    // Batching doesn't change the points sampled
+    let serial = Cuhre::new(1000000).with_domain(domain.clone())
+                                    .integrate(|(x, y): (Real, Real)| vec![x * x * y, x + y],
+                                               1e-10, 1e-12)
+                                    .expect("cuhre should converge");
+    assert_eq!(serial.neval, res.neval);
+
+    let mut divonne = Divonne::new().with_maxeval(1000000).with_domain(domain);
+    let res = divonne.integrate_batch(BatchFn::new(2, 1, |args: &[Real], output: &mut [Real]| {
+        for (x, out) in args.chunks(2).zip(output.iter_mut()) {
+            *out = x[0] * x[0] * x[1];
+        }
+    }), 1e-4, 1e-12).expect("divonne should converge");
+    assert!((res.results[0].value - 2.0 / 3.0).abs() <= 1e-3);
+
+    let wrong_dims = BatchFn::new(3, 1, |_: &[Real], _: &mut [Real]| ());
+    assert_eq!(divonne.integrate_batch(wrong_dims, 1e-4, 1e-12),
+               Err(CubaError::BadDim("divonne", 3)));
+
+    // Panics in the integrand are propagated to the caller
+    let res = panic::catch_unwind(|| {
+        Vegas::default().integrate_batch(BatchFn::new(2, 1, |_: &[Real], _: &mut [Real]| {
+            panic!("integrand failed")
+        }), 1e-4, 1e-12)
+    });
+    assert!(res.is_err());
+}
+
+#[test]
+#[cfg(feature = "cuba")]
+fn test_statefile() {
+    use std::{env, fs, process};
+    use std::path::PathBuf;
+
+    let path = env::temp_dir().join(format!("integrators-statefile-{}", process::id()));
+    let fun = |(x, y): (Real, Real)| x * y;
+
+    // The state file is deleted once integration succeeds, unless retained
+    let mut vegas = Vegas::default().with_maxeval(100000).with_statefile(&path);
+    assert_eq!(vegas.statefile(), Some(path.as_path()));
+    let first = vegas.integrate(fun, 1e-3, 1e-12).expect("vegas should converge");
+    assert!(!path.exists());
+
+    let mut vegas = vegas.with_retain_statefile(true);
+    assert_eq!(vegas.integrate(fun, 1e-3, 1e-12), Ok(first.clone()));
+    assert!(path.exists());
+
+    // Resuming continues from the saved state, rather than starting over
+    let resumed = vegas.integrate(fun, 1e-3, 1e-12).expect("vegas should converge");
+    assert!(resumed.neval >= first.neval);
+    fs::remove_file(&path).expect("state file should exist");
+
+    let mut cuhre = Cuhre::new(100000).with_statefile("bad\0path");
+    assert_eq!(cuhre.integrate(fun, 1e-3, 1e-12),
+               Err(CubaError::BadStatefile(PathBuf::from("bad\0path"))));
+}
+
+#[test]
+#[cfg(feature = "cuba")]
+fn test_vegas_grid_slot() {
+    // A family of narrow Gaussians, centered near (0.3, 0.3)
+    let gaussian = |center: Real| {
+        move |(x, y): (Real, Real)| {
+            (-((x - center).powi(2) + (y - center).powi(2)) / (2.0 * 0.05 * 0.05)).exp()
+        }
+    };
+    let exact = 2.0 * ::std::f64::consts::PI * 0.05 * 0.05;
+
+    let mut vegas = Vegas::default().with_maxeval(10000000).with_grid_slot(3);
+    assert_eq!(vegas.grid_slot(), Some(3));
+    let first = vegas.integrate(gaussian(0.3), 1e-3, 1e-12)
+                     .expect("vegas should converge");
+    assert!((first.results[0].value - exact).abs() <= 1e-2 * exact);
+
+    // A similar integrand starts from the adapted grid
+    let reused = vegas.integrate(gaussian(0.31), 1e-3, 1e-12)
+                      .expect("vegas should converge");
+    assert!((reused.results[0].value - exact).abs() <= 1e-2 * exact);
+
+    // Once cleared, the grid is adapted from scratch again
+    let mut vegas = vegas.with_cleared_grid_slot();
+    assert!(vegas.clears_grid_slot());
+    let cleared = vegas.integrate(gaussian(0.3), 1e-3, 1e-12)
+                       .expect("vegas should converge");
+    assert_eq!(cleared, first);
+    assert!(!vegas.clears_grid_slot());
+
+    assert_eq!(vegas.without_grid_slot().grid_slot(), None);
+}
+
+#[test]
+#[cfg(feature = "cuba")]
+fn test_cuba_flags() {
+    use std::panic;
+
+    let flags = CubaFlags::new().with_verbosity(2)
+                                .with_final_iteration_only(true)
+                                .with_smoothing(false)
+                                .with_retain_statefile(true)
+                                .with_ranlux_level(3);
+    assert_eq!(flags.bits(), 2 | 4 | 8 | 16 | (3 << 8));
+    assert_eq!(flags.verbosity(), 2);
+    assert!(flags.final_iteration_only() && !flags.smoothing() && flags.retain_statefile());
+    assert_eq!(flags.ranlux_level(), 3);
+    assert_eq!(CubaFlags::default().bits(), 0);
+    assert!(panic::catch_unwind(|| CubaFlags::new().with_verbosity(4)).is_err());
+    let flags = CubaFlags::new().with_verbosity(3).with_ranlux_level(CubaFlags::MAX_RANLUX_LEVEL);
+    assert!(flags.bits() as i32 > 0);
+    assert!(panic::catch_unwind(|| {
+        CubaFlags::new().with_ranlux_level(CubaFlags::MAX_RANLUX_LEVEL + 1)
+    }).is_err());
+
+    // The random number source is selected by the seed and the Ranlux level
+    let vegas = Vegas::default().with_seed(7);
+    assert_eq!(vegas.rng(), RandomNumberSource::MersenneTwister);
+    let vegas = vegas.with_rng(RandomNumberSource::Ranlux(2));
+    assert_eq!(vegas.rng(), RandomNumberSource::Ranlux(2));
+    assert_eq!(vegas.flags().ranlux_level(), 2);
+    let vegas = vegas.with_rng(RandomNumberSource::Sobol);
+    assert_eq!(vegas.rng(), RandomNumberSource::Sobol);
+    assert_eq!(vegas.flags().ranlux_level(), 0);
+    assert_eq!(Suave::default().with_rng(RandomNumberSource::MersenneTwister).rng(),
+               RandomNumberSource::MersenneTwister);
+
+    let exact = 1.0 / 9.0;
+    let check = |res: Result<CubaIntegrationResults, CubaError>| {
+        let res = res.expect("integration should converge");
+        assert!((res.results[0].value - exact).abs() <= 1e-2 * exact);
+    };
+    let flags = CubaFlags::new().with_final_iteration_only(true).with_smoothing(false);
+    check(Vegas::default().with_flags(flags)
+                          .with_rng(RandomNumberSource::Ranlux(1))
+                          .integrate(|(x, y): (Real, Real)| x * x * y * y, 1e-3, 1e-12));
+    check(Suave::default().with_flags(flags)
+                          .with_rng(RandomNumberSource::Ranlux(1))
+                          .integrate(|(x, y): (Real, Real)| x * x * y * y, 1e-3, 1e-12));
+    check(Divonne::default().with_flags(flags)
+                            .integrate(|(x, y): (Real, Real)| x * x * y * y, 1e-3, 1e-12));
+    check(Cuhre::new(100000).with_flags(flags)
+                            .integrate(|(x, y): (Real, Real)| x * x * y * y, 1e-3, 1e-12));
+}
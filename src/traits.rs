@@ -1,4 +1,4 @@
-use std::error;
+use std::{error, marker};
 use super::{Real, IntegrationResult};
 
 /// Types which can perform numerical integration can implement this type.
@@ -24,6 +24,96 @@ pub trait Integrator {
               B: IntegrandOutput;
 }
 
+/// An integrand which is evaluated at many points at once, e.g. to make use
+/// of SIMD, or to share work between points, rather than one point at a time
+/// like the closures given to `Integrator::integrate`.
+///
+/// Points are passed as a matrix, in row-major order: `args` holds the
+/// `input_size()` coordinates of the first point, then those of the second,
+/// and so on. The outputs are written to `output` the same way, with
+/// `output_size()` values for each point.
+pub trait BatchIntegrand {
+    /// The number of coordinates of each point.
+    fn input_size(&self) -> usize;
+    /// The number of values the integrand gives at each point.
+    fn output_size(&self) -> usize;
+    /// Evaluates the integrand at `args.len() / input_size()` points, writing
+    /// `output_size()` values per point to `output`.
+    fn evaluate(&mut self, args: &[Real], output: &mut [Real]);
+}
+
+impl<'a, I: BatchIntegrand + ?Sized> BatchIntegrand for &'a mut I {
+    fn input_size(&self) -> usize {
+        (**self).input_size()
+    }
+
+    fn output_size(&self) -> usize {
+        (**self).output_size()
+    }
+
+    fn evaluate(&mut self, args: &[Real], output: &mut [Real]) {
+        (**self).evaluate(args, output)
+    }
+}
+
+/// A `BatchIntegrand` made from a closure, which takes the matrix of points
+/// and the matrix of outputs to fill, and the sizes of each row.
+///
+/// ```
+/// use integrators::{BatchFn, BatchIntegrand, Real};
+///
+/// // f(x, y) = (x + y, x y)
+/// let mut fun = BatchFn::new(2, 2, |args: &[Real], output: &mut [Real]| {
+///     for (x, out) in args.chunks(2).zip(output.chunks_mut(2)) {
+///         out[0] = x[0] + x[1];
+///         out[1] = x[0] * x[1];
+///     }
+/// });
+///
+/// let mut output = [0.0; 4];
+/// fun.evaluate(&[1.0, 2.0, 3.0, 4.0], &mut output);
+/// assert_eq!(output, [3.0, 2.0, 7.0, 12.0]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct BatchFn<F> {
+    input_size: usize,
+    output_size: usize,
+    fun: F,
+}
+
+impl<F: FnMut(&[Real], &mut [Real])> BatchFn<F> {
+    pub fn new(input_size: usize, output_size: usize, fun: F) -> Self {
+        BatchFn { input_size, output_size, fun }
+    }
+}
+
+impl<F: FnMut(&[Real], &mut [Real])> BatchIntegrand for BatchFn<F> {
+    fn input_size(&self) -> usize {
+        self.input_size
+    }
+
+    fn output_size(&self) -> usize {
+        self.output_size
+    }
+
+    fn evaluate(&mut self, args: &[Real], output: &mut [Real]) {
+        (self.fun)(args, output)
+    }
+}
+
+/// Integrators which can evaluate a `BatchIntegrand` at many points at once.
+pub trait BatchIntegrator {
+    /// If integration converges successfully, the integrator will return a
+    /// `Success` value.
+    type Success: IntegrationResults;
+    /// If integration fails, the integrator will return an error type.
+    type Failure: error::Error + 'static;
+    /// Integrates `integrand` to a relative precision of `epsrel`, and an
+    /// absolute precision of `epsabs`, as with `Integrator::integrate`.
+    fn integrate_batch<I>(&mut self, integrand: I, epsrel: Real, epsabs: Real) -> Result<Self::Success, Self::Failure>
+        where I: BatchIntegrand;
+}
+
 pub trait IntegrandInput {
     fn input_size() -> usize;
     fn from_args(&[Real]) -> Self;
@@ -39,6 +129,33 @@ pub trait IntegrationResults {
     fn results(self) -> Self::Iterator;
 }
 
+/// The arguments of an integrand, kept as plain numbers, for adapters which
+/// rearrange or change them before passing them on to another integrand.
+/// How many there are is given by `N`.
+pub(crate) struct RawArgs<N> {
+    pub(crate) args: Vec<Real>,
+    count: marker::PhantomData<N>,
+}
+
+/// The number of arguments of a `RawArgs`.
+pub(crate) trait ArgCount {
+    fn count() -> usize;
+}
+
+impl<N: ArgCount> IntegrandInput for RawArgs<N> {
+    fn input_size() -> usize {
+        N::count()
+    }
+
+    fn from_args(args: &[Real]) -> Self {
+        assert!(args.len() == Self::input_size());
+        RawArgs {
+            args: args.to_vec(),
+            count: marker::PhantomData
+        }
+    }
+}
+
 impl IntegrandOutput for Vec<Real> {
     fn output_size(&self) -> usize {
         self.len()
@@ -0,0 +1,350 @@
+use std::{error, fmt, marker};
+use std::f64::consts::PI;
+
+use super::Real;
+use super::traits::{ArgCount, Integrator, IntegrandInput, IntegrandOutput, RawArgs};
+
+/// A change of variables `x = transform(t)` which maps the unit interval
+/// onto itself, monotonically, fixing both endpoints. Substitutions which
+/// make the Jacobian vanish at an endpoint cluster the integration points
+/// there, and cancel integrable singularities of the integrand, such as
+/// `1 / sqrt(x)` at `x = 0`.
+///
+/// See `Transformed` to apply a transform to an integrator.
+pub trait Transform {
+    /// Maps `t` in [0, 1] to `x` in [0, 1].
+    fn transform(&self, t: Real) -> Real;
+    /// The Jacobian `dx/dt` at `t`.
+    fn jacobian(&self, t: Real) -> Real;
+}
+
+/// The logarithmic substitution `x = (exp(k t) - 1) / (exp(k) - 1)`. For
+/// positive `k`, integration points are concentrated towards `x = 0`; for
+/// negative `k`, towards `x = 1`. This suits integrands which vary over
+/// many orders of magnitude near an endpoint, like `log(x)`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Logarithmic {
+    k: Real,
+}
+
+impl Logarithmic {
+    /// # Panics
+    ///
+    /// Panics if `k` is zero or not finite.
+    pub fn new(k: Real) -> Self {
+        assert!(k != 0.0 && k.is_finite(), "Logarithmic transform needs a finite, nonzero k, got {}", k);
+        Logarithmic { k }
+    }
+
+    pub fn k(&self) -> Real {
+        self.k
+    }
+}
+
+impl Transform for Logarithmic {
+    fn transform(&self, t: Real) -> Real {
+        (self.k * t).exp_m1() / self.k.exp_m1()
+    }
+
+    fn jacobian(&self, t: Real) -> Real {
+        self.k * (self.k * t).exp() / self.k.exp_m1()
+    }
+}
+
+/// The power-law substitution `x = t^p`. For `p > 1`, this cancels a
+/// singularity `x^(-a)` at `x = 0` when `p >= 1 / (1 - a)`; e.g., `p = 2`
+/// makes `1 / sqrt(x)` constant.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Power {
+    p: Real,
+}
+
+impl Power {
+    /// # Panics
+    ///
+    /// Panics unless `p` is positive and finite.
+    pub fn new(p: Real) -> Self {
+        assert!(p > 0.0 && p.is_finite(), "Power transform needs a positive, finite p, got {}", p);
+        Power { p }
+    }
+
+    pub fn p(&self) -> Real {
+        self.p
+    }
+}
+
+impl Transform for Power {
+    fn transform(&self, t: Real) -> Real {
+        t.powf(self.p)
+    }
+
+    fn jacobian(&self, t: Real) -> Real {
+        self.p * t.powf(self.p - 1.0)
+    }
+}
+
+/// The tanh-sinh (double exponential) substitution,
+/// `x = (1 + tanh(pi/2 sinh(s)) / tanh(pi/2 sinh(smax))) / 2` with
+/// `s = smax (2t - 1)`, normalized so that the endpoints are fixed. The
+/// Jacobian decays double-exponentially at both endpoints, which softens
+/// almost any endpoint singularity. `smax` of 3 to 4 is usually enough; much
+/// larger values waste points in regions which round to the endpoints.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TanhSinh {
+    smax: Real,
+}
+
+impl TanhSinh {
+    /// # Panics
+    ///
+    /// Panics unless `smax` is positive and finite.
+    pub fn new(smax: Real) -> Self {
+        assert!(smax > 0.0 && smax.is_finite(), "TanhSinh transform needs a positive, finite smax, got {}", smax);
+        TanhSinh { smax }
+    }
+
+    pub fn smax(&self) -> Real {
+        self.smax
+    }
+}
+
+impl TanhSinh {
+    /// `pi/2 sinh(|s|)` at `t`, and at the ends of the range.
+    fn exponents(&self, t: Real) -> (Real, Real, Real) {
+        let s = self.smax * (2.0 * t - 1.0);
+        (s, PI / 2.0 * s.abs().sinh(), PI / 2.0 * self.smax.sinh())
+    }
+}
+
+impl Transform for TanhSinh {
+    fn transform(&self, t: Real) -> Real {
+        let (s, a, amax) = self.exponents(t);
+        // The distance to the nearest endpoint, written in terms of
+        // `exp(-2a)` to avoid both cancellation and overflow near the ends
+        let dist = (-2.0 * a).exp() * -(-2.0 * (amax - a)).exp_m1()
+                   / ((1.0 - (-2.0 * amax).exp()) * (1.0 + (-2.0 * a).exp()));
+        if s < 0.0 { dist } else { 1.0 - dist }
+    }
+
+    fn jacobian(&self, t: Real) -> Real {
+        let (s, a, amax) = self.exponents(t);
+        // sech(a)^2 = 4 exp(-2a) / (1 + exp(-2a))^2
+        let e = (-2.0 * a).exp();
+        self.smax * PI / 2.0 * s.cosh() * 4.0 * e / ((1.0 + e).powi(2) * amax.tanh())
+    }
+}
+
+/// Sidi's `sin^m` substitution, a polynomial-decay relative of the IMT
+/// transformation, whose Jacobian is proportional to `sin(pi t)^m`. This
+/// vanishes to order `m` at both endpoints, softening singularities there,
+/// and makes the transformed integrand periodic, which suits lattice rules
+/// and other equal-weight sampling.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Sidi {
+    m: u32,
+}
+
+impl Sidi {
+    pub fn new(m: u32) -> Self {
+        Sidi { m }
+    }
+
+    pub fn m(&self) -> u32 {
+        self.m
+    }
+
+    /// `\int_0^t sin(pi u)^m du`, by the standard reduction formula.
+    fn integral(m: u32, t: Real) -> Real {
+        match m {
+            0 => t,
+            1 => (1.0 - (PI * t).cos()) / PI,
+            _ => {
+                let m_r = m as Real;
+                -(PI * t).sin().powi(m as i32 - 1) * (PI * t).cos() / (PI * m_r)
+                    + (m_r - 1.0) / m_r * Sidi::integral(m - 2, t)
+            }
+        }
+    }
+}
+
+impl Transform for Sidi {
+    fn transform(&self, t: Real) -> Real {
+        Sidi::integral(self.m, t) / Sidi::integral(self.m, 1.0)
+    }
+
+    fn jacobian(&self, t: Real) -> Real {
+        (PI * t).sin().powi(self.m as i32) / Sidi::integral(self.m, 1.0)
+    }
+}
+
+impl<T: Transform + ?Sized> Transform for Box<T> {
+    fn transform(&self, t: Real) -> Real {
+        (**self).transform(t)
+    }
+
+    fn jacobian(&self, t: Real) -> Real {
+        (**self).jacobian(t)
+    }
+}
+
+/// All the arguments of an integrand `A`, before they are transformed.
+struct Untransformed<A>(marker::PhantomData<A>);
+
+impl<A: IntegrandInput> ArgCount for Untransformed<A> {
+    fn count() -> usize {
+        A::input_size()
+    }
+}
+
+/// Wraps an integrator over the unit hypercube (such as any of the Cuba
+/// integrators without a domain, or nested integrators over [0, 1]),
+/// applying a `Transform` to each dimension of the integrand, and
+/// multiplying it by the Jacobian. The integral is unchanged, but can be
+/// much easier to compute.
+///
+/// Points where the Jacobian vanishes contribute nothing to the integral, and
+/// the integrand is not called there; so a singularity at an endpoint which
+/// is cancelled by the transform is never evaluated. The integrand is called
+/// once before integrating, at the transformed center of the hypercube, to
+/// find its number of outputs (as the Cuba integrators do).
+///
+/// ```
+/// # #[cfg(feature = "cuba")]
+/// # fn main() {
+/// use integrators::{Integrator, Power, Real, Transformed};
+/// use integrators::cuba::Cuhre;
+///
+/// // \int_0^1 \int_0^1 1 / sqrt(x y) dx dy = 4
+/// let mut cuhre = Transformed::new(Cuhre::new(100000), Power::new(2.0));
+/// let res = cuhre.integrate(|(x, y): (Real, Real)| (x * y).sqrt().recip(), 1e-8, 1e-12)
+///                .unwrap();
+/// assert!((res.results[0].value - 4.0).abs() < 1e-6);
+/// # }
+/// # #[cfg(not(feature = "cuba"))]
+/// # fn main() {}
+/// ```
+#[derive(Debug, Clone)]
+pub struct Transformed<I, T> {
+    integrator: I,
+    transforms: Vec<T>,
+    every_dimension: bool,
+}
+
+impl<I, T: Transform> Transformed<I, T> {
+    /// Applies `transform` to every dimension of the integrand.
+    pub fn new(integrator: I, transform: T) -> Self {
+        Transformed {
+            integrator,
+            transforms: vec![transform],
+            every_dimension: true,
+        }
+    }
+
+    /// Applies one transform to each dimension of the integrand, in order.
+    /// To mix different kinds of transforms, use `Box<Transform>`.
+    ///
+    /// Integration fails with `TransformedError::BadDim`, without calling
+    /// the integrator, unless there is exactly one transform for each input
+    /// of the integrand.
+    pub fn per_dimension<J>(integrator: I, transforms: J) -> Self
+        where J: IntoIterator<Item=T> {
+        Transformed {
+            integrator,
+            transforms: transforms.into_iter().collect(),
+            every_dimension: false,
+        }
+    }
+
+    pub fn integrator(&self) -> &I {
+        &self.integrator
+    }
+
+    pub fn integrator_mut(&mut self) -> &mut I {
+        &mut self.integrator
+    }
+
+    pub fn into_inner(self) -> I {
+        self.integrator
+    }
+}
+
+impl<I, T> Integrator for Transformed<I, T>
+    where I: Integrator,
+          T: Transform
+{
+    type Success = I::Success;
+    type Failure = TransformedError<I::Failure>;
+    fn integrate<A, B, F: FnMut(A) -> B>(&mut self, mut fun: F, epsrel: Real, epsabs: Real) -> Result<Self::Success, Self::Failure>
+        where A: IntegrandInput,
+              B: IntegrandOutput
+    {
+        let ndim = A::input_size();
+        if !self.every_dimension && self.transforms.len() != ndim {
+            return Err(TransformedError::BadDim(self.transforms.len(), ndim))
+        }
+
+        let Transformed { ref mut integrator, ref transforms, every_dimension } = *self;
+        let transform_for = |dim: usize| {
+            if every_dimension { &transforms[0] } else { &transforms[dim] }
+        };
+
+        // Points where the Jacobian vanishes are never evaluated, so find the
+        // number of outputs first, at the center of the unit hypercube
+        let mut x: Vec<Real> = (0..ndim).map(|i| transform_for(i).transform(0.5)).collect();
+        let ncomp = fun(A::from_args(&x[..])).output_size();
+
+        integrator.integrate(|raw: RawArgs<Untransformed<A>>| {
+            let mut jacobian = 1.0;
+            for (i, (&t, x)) in raw.args.iter().zip(x.iter_mut()).enumerate() {
+                let transform = transform_for(i);
+                *x = transform.transform(t);
+                jacobian *= transform.jacobian(t);
+            }
+
+            let mut output = vec![0.0; ncomp];
+            // Where the transform cancels the integrand, it contributes nothing
+            if jacobian == 0.0 {
+                return output
+            }
+
+            fun(A::from_args(&x[..])).into_args(&mut output[..]);
+            for out in output.iter_mut() {
+                *out *= jacobian;
+            }
+            output
+        }, epsrel, epsabs).map_err(TransformedError::Inner)
+    }
+}
+
+/// The failure of a `Transformed` integrator.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TransformedError<E> {
+    /// The wrapped integrator failed.
+    Inner(E),
+    /// `Transformed::per_dimension` was given a number of transforms (the
+    /// first field) different from the number of inputs of the integrand
+    /// (the second).
+    BadDim(usize, usize),
+}
+
+impl<E: fmt::Display> fmt::Display for TransformedError<E> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            &TransformedError::Inner(ref e) => write!(fmt, "{}", e),
+            &TransformedError::BadDim(transforms, inputs) =>
+                write!(fmt, "{} transforms given for an integrand with {} inputs",
+                       transforms, inputs),
+        }
+    }
+}
+
+impl<E> error::Error for TransformedError<E>
+    where E: error::Error + 'static
+{
+    fn source(&self) -> Option<&(error::Error + 'static)> {
+        match self {
+            &TransformedError::Inner(ref e) => Some(e),
+            &TransformedError::BadDim(..) => None,
+        }
+    }
+}